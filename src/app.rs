@@ -5,8 +5,11 @@ use leptos_router::*;
 
 // Import our components
 use crate::components::about::AboutPage;
+use crate::components::calendar::CalendarPage;
 use crate::components::exercises::ExercisesPage;
+use crate::components::history_page::HistoryPage;
 use crate::components::home::HomePage;
+use crate::components::routine_editor::RoutineEditorPage;
 use crate::components::science::SciencePage;
 use crate::components::settings::{SettingsPage, SettingsProvider};
 use crate::components::timer::TimerPage;
@@ -54,6 +57,13 @@ pub fn App() -> impl IntoView {
                   <Route path=path!("/settings") view=move || view! { <SettingsPage /> } />
                   <Route path=path!("/science") view=move || view! { <SciencePage /> } />
                   <Route path=path!("/exercises") view=move || view! { <ExercisesPage /> } />
+                  <Route path=path!("/history") view=move || view! { <HistoryPage /> } />
+                  <Route path=path!("/calendar") view=move || view! { <CalendarPage /> } />
+                  <Route path=path!("/routines/new") view=move || view! { <RoutineEditorPage /> } />
+                  <Route
+                    path=path!("/routines/:id/edit")
+                    view=move || view! { <RoutineEditorPage /> }
+                  />
                 </Routes>
               </main>
             </Router>
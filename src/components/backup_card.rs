@@ -0,0 +1,134 @@
+use crate::components::settings::SettingsContext;
+use crate::data::backup::{merge, Backup, MergeReport};
+use chrono::Utc;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, Url};
+
+/// Result of the last import, shown under the buttons.
+#[derive(Clone, Debug, PartialEq)]
+enum ImportStatus {
+    Merged(MergeReport),
+    Failed(String),
+}
+
+// Offer `contents` as a file download
+fn download(filename: &str, contents: &str) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or("no window")?;
+    let document = window.document().ok_or("no document")?;
+
+    let options = BlobPropertyBag::new();
+    options.set_type("application/json");
+    let blob = Blob::new_with_str_sequence_and_options(&js_sys::Array::of1(&contents.into()), &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let anchor: HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    Url::revoke_object_url(&url)
+}
+
+/// Export everything stored locally to a JSON file, and merge such a file back in.
+#[component]
+pub fn BackupCard() -> impl IntoView {
+    let SettingsContext {
+        settings,
+        update_settings,
+        ..
+    } = expect_context::<SettingsContext>();
+
+    let status = RwSignal::new(None::<ImportStatus>);
+
+    let export = move |_| {
+        let now = Utc::now();
+        let json = Backup::new(&settings.get_untracked(), now).to_json();
+        let filename = format!("hiit-backup-{}.json", now.format("%Y-%m-%d"));
+        if let Err(err) = download(&filename, &json) {
+            log::warn!("Failed to export data: {:?}", err);
+        }
+    };
+
+    let import = move |ev: leptos::ev::Event| {
+        let input: HtmlInputElement = event_target(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        // Allow importing the same file again
+        input.set_value("");
+        spawn_local(async move {
+            let text = match JsFuture::from(file.text()).await {
+                Ok(text) => text.as_string().unwrap_or_default(),
+                Err(err) => {
+                    status.set(Some(ImportStatus::Failed(format!("Could not read file: {:?}", err))));
+                    return;
+                }
+            };
+            match Backup::parse(&text) {
+                Ok(backup) => {
+                    let (merged, report) = merge(&settings.get_untracked(), backup.settings);
+                    update_settings.run(merged);
+                    status.set(Some(ImportStatus::Merged(report)));
+                }
+                Err(err) => status.set(Some(ImportStatus::Failed(err.to_string()))),
+            }
+        });
+    };
+
+    view! {
+      <div class="p-6 mb-6 bg-white rounded-lg shadow-md">
+        <h2 class="mb-2 text-lg font-semibold text-gray-800">Your Data</h2>
+        <p class="mb-4 text-sm text-gray-600">
+          Settings, custom routines and workout history are only stored in this browser.
+          Export them to a file to move them to another device.
+        </p>
+
+        <div class="flex space-x-4">
+          <button
+            class="py-2 px-4 font-semibold text-white bg-blue-600 rounded-lg transition-colors hover:bg-blue-700"
+            on:click=export
+          >
+            Export
+          </button>
+          <label class="py-2 px-4 font-semibold text-gray-700 bg-white rounded-lg border border-gray-300 transition-colors cursor-pointer hover:bg-gray-50">
+            Import
+            <input type="file" accept="application/json,.json" class="hidden" on:change=import />
+          </label>
+        </div>
+
+        {move || {
+          status
+            .get()
+            .map(|status| match status {
+              ImportStatus::Merged(report) => {
+                view! {
+                  <div class="p-3 mt-4 text-sm text-green-800 bg-green-50 rounded-md">
+                    <p>
+                      {format!(
+                        "Imported {} new routines and {} workouts.",
+                        report.routines_added,
+                        report.history_added,
+                      )}
+                    </p>
+                    <ul class="mt-2 list-disc list-inside text-yellow-800">
+                      {report
+                        .conflicts
+                        .into_iter()
+                        .map(|conflict| view! { <li>{conflict}</li> })
+                        .collect::<Vec<_>>()}
+                    </ul>
+                  </div>
+                }
+                  .into_any()
+              }
+              ImportStatus::Failed(err) => {
+                view! { <div class="p-3 mt-4 text-sm text-red-800 bg-red-50 rounded-md">{err}</div> }
+                  .into_any()
+              }
+            })
+        }}
+      </div>
+    }
+}
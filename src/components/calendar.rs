@@ -0,0 +1,88 @@
+use crate::components::settings::SettingsContext;
+use crate::workout::history::{daily_work_secs, heat_level, week_start};
+use chrono::{Local, NaiveDate};
+use leptos::prelude::*;
+
+/// Columns in the heatmap: a year of weeks plus the current one.
+const WEEKS: usize = 53;
+
+fn level_class(level: u8) -> &'static str {
+    match level {
+        0 => "bg-gray-100",
+        1 => "bg-green-200",
+        2 => "bg-green-400",
+        3 => "bg-green-600",
+        _ => "bg-green-800",
+    }
+}
+
+/// GitHub-style grid of the last year, one cell per day, shaded by the
+/// high-intensity time of the workouts finished that day.
+#[component]
+pub fn CalendarHeatmap() -> impl IntoView {
+    let SettingsContext { settings, .. } = expect_context::<SettingsContext>();
+
+    // The local date is only known on the client; the server renders an empty grid
+    let today = RwSignal::new(None::<NaiveDate>);
+    Effect::new(move |_| today.set(Some(Local::now().date_naive())));
+
+    let work_by_day = Memo::new(move |_| {
+        let offset = *Local::now().fixed_offset().offset();
+        settings.with(|s| daily_work_secs(&s.history, offset))
+    });
+
+    let cell = move |week: usize, weekday: usize| {
+        let Some(today) = today.get() else {
+            return view! { <div class=format!("w-3 h-3 rounded-sm {}", level_class(0))></div> }.into_any();
+        };
+        let first_monday = week_start(today) - chrono::Duration::weeks(WEEKS as i64 - 1);
+        let date = first_monday + chrono::Duration::days((week * 7 + weekday) as i64);
+        if date > today {
+            return view! { <div class="w-3 h-3"></div> }.into_any();
+        }
+        let work_secs = work_by_day.with(|days| days.get(&date).copied().unwrap_or_default());
+        let title = format!("{}: {} min", date.format("%a %-d %b %Y"), work_secs / 60);
+        view! { <div class=format!("w-3 h-3 rounded-sm {}", level_class(heat_level(work_secs))) title=title></div> }
+            .into_any()
+    };
+
+    view! {
+      <div class="overflow-x-auto">
+        <div class="flex gap-1">
+          {(0..WEEKS)
+            .map(|week| {
+              view! {
+                <div class="flex flex-col gap-1">
+                  {(0..7).map(|weekday| move || cell(week, weekday)).collect::<Vec<_>>()}
+                </div>
+              }
+            })
+            .collect::<Vec<_>>()}
+        </div>
+      </div>
+      <div class="flex justify-end items-center mt-2 space-x-1 text-xs text-gray-500">
+        <span class="mr-1">Less</span>
+        {(0..=4).map(|level| view! { <div class=format!("w-3 h-3 rounded-sm {}", level_class(level))></div> }).collect::<Vec<_>>()}
+        <span class="ml-1">More</span>
+      </div>
+    }
+}
+
+#[component]
+pub fn CalendarPage() -> impl IntoView {
+    view! {
+      <div class="container py-8 px-4 mx-auto max-w-4xl">
+        <h1 class="mb-6 text-2xl font-bold text-center text-gray-800 md:text-3xl">Workout Calendar</h1>
+
+        <div class="p-6 mb-6 bg-white rounded-lg shadow-md">
+          <CalendarHeatmap />
+        </div>
+
+        <div class="text-center">
+          <a href="/" class="text-blue-600 hover:text-blue-800">
+            Return to Home
+          </a>
+        </div>
+      </div>
+    }
+}
@@ -6,6 +6,8 @@ pub struct Exercise {
     pub group: &'static str,
     pub name: &'static str,
     pub description: &'static str,
+    /// Worked one side at a time, so the work time is split between left and right.
+    pub unilateral: bool,
 }
 
 pub const EXERCISES: [Exercise; 22] = [
@@ -18,6 +20,7 @@ pub const EXERCISES: [Exercise; 22] = [
              until your chest nearly touches the ground, then push back up \
              to starting position. Keep your neck neutral by looking slightly \
              ahead rather than down.",
+        unilateral: false,
     },
     Exercise {
         group: "Upper Body",
@@ -33,6 +36,7 @@ pub const EXERCISES: [Exercise; 22] = [
              For increased difficulty: use heavier weights, slow down the lowering phase, \
              or perform alternating arms. For reduced intensity: use lighter weights, \
              perform seated hammer curls, or reduce the range of motion.",
+        unilateral: false,
     },
     Exercise {
         group: "Upper Body",
@@ -48,6 +52,7 @@ pub const EXERCISES: [Exercise; 22] = [
              or perform single-arm skull crushers. For reduced intensity: use lighter weights, \
              perform seated skull crushers, or reduce the range of motion. \
              For a variation, try performing them with a resistance band or with your feet elevated.",
+        unilateral: false,
     },
     Exercise {
         group: "Upper Body",
@@ -63,6 +68,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Exhale as you extend your arms, inhale as you lower the weight. \
              For less intensity, use a lighter weight; for more challenge, increase the weight \
              or slow down the tempo, especially during the lowering phase.",
+        unilateral: false,
     },
     Exercise {
         group: "Upper Body",
@@ -76,6 +82,7 @@ pub const EXERCISES: [Exercise; 22] = [
              For proper form, keep your upper arms stationary, back straight, and wrists neutral. \
              To increase difficulty, use heavier weights or slow down the lowering phase. \
              For a variation, try alternating arms or rotating your wrists during the curl (hammer to supinated).",
+        unilateral: false,
     },
     Exercise {
         group: "Core",
@@ -85,6 +92,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Keep your core engaged, back flat (avoid sagging or lifting hips), and neck neutral \
              by looking at the floor slightly ahead of your hands. Squeeze your glutes and quads \
              to maintain proper form. Hold this position while breathing steadily.",
+        unilateral: false,
     },
     Exercise {
         group: "Core",
@@ -95,6 +103,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Keeping your back straight, rotate your torso from side to side, touching your \
              hands to the ground on each side. The movement should come from your core, not \
              your arms. Keep your feet steady and maintain balance throughout the exercise.",
+        unilateral: false,
     },
     Exercise {
         group: "Core",
@@ -107,6 +116,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Lift only until your shoulder blades clear the floor (about 30 degrees). \
              Hold briefly at the top, then inhale as you slowly lower back down with control. \
              For a greater challenge, lift your feet off the floor or extend your arms overhead.",
+        unilateral: false,
     },
     Exercise {
         group: "Core",
@@ -120,6 +130,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Focus on the rotation of your torso rather than just the elbow-to-knee connection. \
              Keep movements controlled rather than rushing through repetitions. \
              For less intensity, keep the movements smaller; for more challenge, extend the legs fully.",
+        unilateral: false,
     },
     Exercise {
         group: "Lower Body",
@@ -132,6 +143,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Keep your back straight and head neutral throughout the movement. \
              Push through your heels to return to the starting position, squeezing \
              your glutes at the top.",
+        unilateral: false,
     },
     Exercise {
         group: "Lower Body",
@@ -146,6 +158,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Exhale as you exert effort (pushing back up), inhale as you lower. \
              For less intensity, take a smaller step or don't lower as deep. \
              For more challenge, hold weights, perform walking lunges, or add a jump as you switch legs.",
+        unilateral: true,
     },
     Exercise {
         group: "Lower Body",
@@ -157,6 +170,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Then slowly lower your heels back to the ground in a controlled motion. \
              For added difficulty, perform on a step with heels hanging off the edge to increase range of motion, \
              or try single-leg calf raises for greater intensity.",
+        unilateral: false,
     },
     Exercise {
         group: "Lower Body",
@@ -170,6 +184,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Lower your knees back to the starting position with control. \
              For increased difficulty, hold the top position longer or add small pulses. \
              For reduced intensity, perform smaller movements or take shorter breaks between repetitions.",
+        unilateral: false,
     },
     Exercise {
         group: "Full Body",
@@ -181,6 +196,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Keep your hips low and avoid letting them rise too high. Breathe rhythmically \
              and maintain a steady pace. Focus on keeping your wrists aligned under your shoulders \
              to reduce strain. For increased intensity, speed up the pace while maintaining proper form.",
+        unilateral: false,
     },
     Exercise {
         group: "Full Body",
@@ -192,6 +208,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Lower back down with control and repeat. Breathe out as you lift and in as you lower. \
              For a modified version, lift only your arms or only your legs. For increased difficulty, \
              hold the raised position longer or add small pulses at the top of the movement.",
+        unilateral: false,
     },
     Exercise {
         group: "Full Body",
@@ -205,6 +222,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Breathe naturally throughout the movement. For an easier version, bend your knees more. \
              For added difficulty, add a push-up when in the plank position or take smaller hand steps \
              to increase time under tension.",
+        unilateral: false,
     },
     Exercise {
         group: "Full Body",
@@ -216,6 +234,7 @@ pub const EXERCISES: [Exercise; 22] = [
              from the squat, reaching your arms overhead. Land softly and immediately begin the next rep. \
              For a modified version, step back and forward instead of jumping, or skip the push-up. \
              For increased intensity, add a tuck jump at the top or perform the push-up with a clap.",
+        unilateral: false,
     },
     Exercise {
         group: "Mobility",
@@ -228,6 +247,7 @@ pub const EXERCISES: [Exercise; 22] = [
              Repeat the movement in a controlled manner. For increased difficulty, try to straighten your legs completely. \
              For reduced intensity, maintain a slight bend in the knees at the top position. \
              Focus on keeping your breathing steady throughout the exercise.",
+        unilateral: false,
     },
     Exercise {
         group: "Mobility",
@@ -240,6 +260,7 @@ pub const EXERCISES: [Exercise; 22] = [
              For an easier version, perform Downward Dog with bent knees or substitute Cobra pose for Upward Dog by keeping \
              your hips on the ground. For increased difficulty, hold each position longer, add a plank between transitions, \
              or lift one leg during Downward Dog to challenge balance and engage your core more deeply.",
+        unilateral: false,
     },
     Exercise {
         group: "Mobility",
@@ -255,6 +276,7 @@ pub const EXERCISES: [Exercise; 22] = [
              For an easier version, take a smaller step forward or don't lower as deeply into the lunge. \
              For increased difficulty, add a twist toward the front leg while in the lunge position, \
              or hold the position longer to increase the stretch and challenge your balance.",
+        unilateral: true,
     },
     Exercise {
         group: "Mobility",
@@ -267,6 +289,7 @@ pub const EXERCISES: [Exercise; 22] = [
             relaxing your hip muscles with each exhale. For increased comfort, start with a folded blanket or \
             cushion under your sitting bones. For a deeper stretch, gently press down on your inner thighs or \
             extend your torso forward while maintaining a straight back.",
+        unilateral: false,
     },
     Exercise {
         group: "Mobility",
@@ -281,9 +304,17 @@ pub const EXERCISES: [Exercise; 22] = [
              your legs to stretch the opposite hip. For beginners, use your hands for support and don't \
              lean too far forward. For increased difficulty, try transitioning smoothly between sides or \
              hold the position longer.",
+        unilateral: true,
     }
 ];
 
+/// Look up an exercise by name, ignoring case.
+pub fn find_exercise(name: &str) -> Option<&'static Exercise> {
+    EXERCISES
+        .iter()
+        .find(|exercise| exercise.name.eq_ignore_ascii_case(name))
+}
+
 #[component]
 pub fn ExercisesPage() -> impl IntoView {
     let exercises_by_group = Memo::new(move |_| {
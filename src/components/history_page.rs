@@ -0,0 +1,125 @@
+use crate::components::routine_card::{format_days_ago, format_duration};
+use crate::components::settings::SettingsContext;
+use crate::workout::history::HistoryStats;
+use leptos::prelude::*;
+
+/// Past workouts, newest first, with totals across the whole history.
+#[component]
+pub fn HistoryPage() -> impl IntoView {
+    let SettingsContext { settings, .. } = expect_context::<SettingsContext>();
+
+    // Get current time once for the page
+    let current_time = chrono::Local::now();
+
+    let stats = Memo::new(move |_| settings.with(|s| HistoryStats::new(&s.history, current_time.fixed_offset())));
+
+    let stat_tile = |label: &'static str, value: String| {
+        view! {
+          <div class="p-4 text-center bg-gray-50 rounded-lg">
+            <div class="text-2xl font-bold text-gray-800">{value}</div>
+            <div class="text-xs text-gray-500 uppercase">{label}</div>
+          </div>
+        }
+    };
+
+    view! {
+      <div class="container py-8 px-4 mx-auto max-w-2xl">
+        <h1 class="mb-6 text-2xl font-bold text-center text-gray-800 md:text-3xl">Workout History</h1>
+
+        <div class="p-6 mb-6 bg-white rounded-lg shadow-md">
+          <div class="grid grid-cols-2 gap-4 md:grid-cols-4">
+            {move || {
+              let stats = stats.get();
+              view! {
+                {stat_tile("Workouts", stats.workouts.to_string())}
+                {stat_tile("Per week", format!("{:.1}", stats.workouts_per_week))}
+                {stat_tile("Work minutes", (stats.work_secs / 60).to_string())}
+                {stat_tile(
+                  "Longest gap",
+                  stats
+                    .longest_gap_days
+                    .map(|days| format!("{}d", days))
+                    .unwrap_or_else(|| "-".to_string()),
+                )}
+              }
+            }}
+          </div>
+        </div>
+
+        <Show when=move || !stats.read().routine_counts.is_empty()>
+          <div class="p-6 mb-6 bg-white rounded-lg shadow-md">
+            <h2 class="mb-4 text-lg font-semibold text-gray-800">By Routine</h2>
+            <ul class="space-y-2">
+              {move || {
+                stats
+                  .get()
+                  .routine_counts
+                  .into_iter()
+                  .map(|(name, count)| {
+                    view! {
+                      <li class="flex justify-between text-gray-700">
+                        <span>{name}</span>
+                        <span class="font-semibold">{count}</span>
+                      </li>
+                    }
+                  })
+                  .collect::<Vec<_>>()
+              }}
+            </ul>
+          </div>
+        </Show>
+
+        <div class="p-6 mb-6 bg-white rounded-lg shadow-md">
+          <h2 class="mb-4 text-lg font-semibold text-gray-800">Sessions</h2>
+          {move || {
+            let history = settings.get().history;
+            if history.is_empty() {
+              return view! { <p class="text-sm text-gray-600">No workouts yet.</p> }.into_any();
+            }
+            view! {
+              <ul class="divide-y divide-gray-200">
+                {history
+                  .into_iter()
+                  .rev()
+                  .map(|record| {
+                    let duration = (record.ended_at - record.started_at).to_std().unwrap_or_default();
+                    let mut status = if record.finished {
+                      "Finished".to_string()
+                    } else {
+                      format!("Stopped after {}/{} stages", record.stages_completed, record.total_stages)
+                    };
+                    if !record.rounds.is_empty() {
+                      status.push_str(&format!(" · {} rounds", record.total_rounds()));
+                    }
+                    view! {
+                      <li class="flex justify-between items-center py-3">
+                        <div>
+                          <div class="font-medium text-gray-800">{record.routine_name}</div>
+                          <div class="text-sm text-gray-500">{status}</div>
+                        </div>
+                        <div class="text-right">
+                          <div class="text-sm text-gray-700">
+                            {format_days_ago(record.ended_at, current_time)}
+                          </div>
+                          <div class="text-xs text-gray-500">
+                            {format_duration(duration)}
+                          </div>
+                        </div>
+                      </li>
+                    }
+                  })
+                  .collect::<Vec<_>>()}
+              </ul>
+            }
+              .into_any()
+          }}
+        </div>
+
+        <div class="text-center">
+          <a href="/" class="text-blue-600 hover:text-blue-800">
+            Return to Home
+          </a>
+        </div>
+      </div>
+    }
+}
@@ -1,11 +1,54 @@
 use crate::components::routine_card::{Routine, RoutineCard};
-use crate::data::routines::get_routines;
+use crate::components::settings::SettingsContext;
+use crate::data::routines::{all_routines, find_routine};
+use crate::workout::history::{goal_streak, workouts_this_week};
+use crate::workout::session::{SavedSession, WorkoutSession};
+use chrono::Utc;
 use leptos::prelude::*;
 use leptos_router::hooks::use_navigate;
 
 #[component]
 pub fn HomePage() -> impl IntoView {
-    let routines = get_routines();
+    let SettingsContext {
+        settings,
+        update_settings,
+        loaded,
+    } = expect_context::<SettingsContext>();
+
+    // Interrupted workout that can be resumed, loaded on the client side once the
+    // stored settings are in, since an expired one is logged to their history
+    let resumable = RwSignal::new(None::<(WorkoutSession, Routine)>);
+    Effect::new(move |_| {
+        if !loaded.get() {
+            return;
+        }
+        let mut settings = settings.get();
+        let session = match WorkoutSession::load(Utc::now(), settings.resume_window_mins) {
+            Some(SavedSession::Resumable(session)) => Some(session),
+            Some(SavedSession::Expired(session)) => {
+                settings.history.push(session.record(&settings));
+                update_settings.run(settings.clone());
+                None
+            }
+            None => None,
+        };
+        resumable.set(session.and_then(|session| {
+            let routine = find_routine(&session.routine_id, &settings)?;
+            Some((session, routine))
+        }));
+    });
+
+    // Progress towards this week's goal, in the local week
+    let current_time = chrono::Local::now().fixed_offset();
+    let weekly_progress = Memo::new(move |_| {
+        settings.with(|s| {
+            (
+                workouts_this_week(&s.history, current_time),
+                s.weekly_goal,
+                goal_streak(&s.history, s.weekly_goal, current_time),
+            )
+        })
+    });
 
     let navigate = use_navigate();
 
@@ -25,17 +68,76 @@ pub fn HomePage() -> impl IntoView {
           <p class="mt-2 text-center text-gray-600">Choose a workout to begin</p>
         </header>
 
-        <div class="grid grid-cols-1 gap-4 px-4 sm:grid-cols-2 lg:grid-cols-3">
-          {routines
-            .into_iter()
-            .map(|ex| {
+        {move || {
+          resumable
+            .get()
+            .map(|(session, routine)| {
+              let elapsed = session.elapsed().as_secs();
               view! {
-                <div class="h-full">
-                  <RoutineCard routine=StoredValue::new(ex) on_click=card_callback />
+                <div class="px-4 mb-4">
+                  <div class="flex justify-between items-center p-4 bg-white rounded-lg shadow-md">
+                    <span class="text-gray-700">
+                      {format!("Unfinished {} workout at {:02}:{:02}", routine.name, elapsed / 60, elapsed % 60)}
+                    </span>
+                    <a
+                      href=format!("/timer/{}?resume=true", routine.id)
+                      class="py-2 px-4 font-semibold text-white bg-blue-600 rounded-lg transition-colors hover:bg-blue-700"
+                    >
+                      "Resume workout"
+                    </a>
+                  </div>
                 </div>
               }
             })
-            .collect::<Vec<_>>()}
+        }}
+
+        <div class="px-4 mb-4">
+          <a href="/history" class="block p-4 bg-white rounded-lg shadow-md">
+            {move || {
+              let (done, goal, streak) = weekly_progress.get();
+              let progress_pct = (done.min(goal) as f32 / goal.max(1) as f32) * 100.0;
+              view! {
+                <div class="flex justify-between mb-2 text-sm text-gray-700">
+                  <span>{format!("This week: {} of {} workouts", done, goal)}</span>
+                  <span class="font-semibold">
+                    {match streak {
+                      0 => "No streak yet".to_string(),
+                      1 => "1 week streak".to_string(),
+                      n => format!("{} week streak", n),
+                    }}
+                  </span>
+                </div>
+                <div class="overflow-hidden h-2 bg-gray-200 rounded-full">
+                  <div
+                    class="h-full bg-gradient-to-r from-blue-500 to-indigo-600"
+                    style:width=format!("{}%", progress_pct)
+                  ></div>
+                </div>
+              }
+            }}
+          </a>
+        </div>
+
+        <div class="grid grid-cols-1 gap-4 px-4 sm:grid-cols-2 lg:grid-cols-3">
+          {move || {
+            settings
+              .with(all_routines)
+              .into_iter()
+              .map(|ex| {
+                view! {
+                  <div class="h-full">
+                    <RoutineCard routine=StoredValue::new(ex) on_click=card_callback />
+                  </div>
+                }
+              })
+              .collect::<Vec<_>>()
+          }}
+          <a
+            href="/routines/new"
+            class="flex justify-center items-center p-4 h-full font-semibold text-gray-500 rounded-lg border-2 border-gray-300 border-dashed transition-colors hover:text-gray-700 hover:border-gray-400"
+          >
+            "+ New Routine"
+          </a>
         </div>
 
         <footer class="pt-4 mt-8 border-t border-gray-200">
@@ -46,6 +148,12 @@ pub fn HomePage() -> impl IntoView {
             <a href="/settings" class="transition-colors hover:text-gray-900">
               Settings
             </a>
+            <a href="/history" class="transition-colors hover:text-gray-900">
+              History
+            </a>
+            <a href="/calendar" class="transition-colors hover:text-gray-900">
+              Calendar
+            </a>
             <a href="/science" class="transition-colors hover:text-gray-900">
               Science
             </a>
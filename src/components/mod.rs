@@ -1,7 +1,11 @@
 pub mod about;
+pub mod backup_card;
+pub mod calendar;
 pub mod exercises;
+pub mod history_page;
 pub mod home;
 pub mod routine_card;
+pub mod routine_editor;
 pub mod science;
 pub mod screen_wake_lock;
 pub mod settings;
@@ -3,126 +3,281 @@ use itertools::Itertools;
 use leptos::prelude::*;
 use std::time::Duration;
 
-use super::settings::SettingsContext;
+use super::settings::{
+    clamp, SettingsContext, WorkoutSettings, PREPARE_DURATION_SECS, REPS, REST_DURATION_SECS, SETS, WORK_DURATION_SECS,
+};
+use crate::workout::history;
+use crate::workout::schedule::{Schedule, WorkoutMode};
+
+/// Timing values a routine uses instead of the global `WorkoutSettings`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TimingOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_intensity_duration_secs: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rest_exercise_duration_secs: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rest_set_duration_secs: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prepare_duration_secs: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sets: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<WorkoutMode>,
+    /// Work duration of each set in turn, e.g. a 20/30/40/30/20 pyramid. When set,
+    /// it replaces the work duration and decides the number of sets.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub work_ladder: Vec<u32>,
+}
+
+impl TimingOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Field-wise combination, preferring values from `self`.
+    pub fn or(&self, other: &TimingOverrides) -> TimingOverrides {
+        TimingOverrides {
+            high_intensity_duration_secs: self.high_intensity_duration_secs.or(other.high_intensity_duration_secs),
+            rest_exercise_duration_secs: self.rest_exercise_duration_secs.or(other.rest_exercise_duration_secs),
+            rest_set_duration_secs: self.rest_set_duration_secs.or(other.rest_set_duration_secs),
+            prepare_duration_secs: self.prepare_duration_secs.or(other.prepare_duration_secs),
+            sets: self.sets.or(other.sets),
+            mode: self.mode.clone().or(other.mode.clone()),
+            work_ladder: if self.work_ladder.is_empty() {
+                other.work_ladder.clone()
+            } else {
+                self.work_ladder.clone()
+            },
+        }
+    }
+
+    /// The same overrides with every value inside its slider's range.
+    pub fn clamped(self) -> TimingOverrides {
+        TimingOverrides {
+            high_intensity_duration_secs: self.high_intensity_duration_secs.map(|v| clamp(v, &WORK_DURATION_SECS)),
+            rest_exercise_duration_secs: self.rest_exercise_duration_secs.map(|v| clamp(v, &REST_DURATION_SECS)),
+            rest_set_duration_secs: self.rest_set_duration_secs.map(|v| clamp(v, &REST_DURATION_SECS)),
+            prepare_duration_secs: self.prepare_duration_secs.map(|v| clamp(v, &PREPARE_DURATION_SECS)),
+            sets: self.sets.map(|v| clamp(v, &SETS)),
+            work_ladder: self
+                .work_ladder
+                .into_iter()
+                .take(*SETS.end() as usize)
+                .map(|v| clamp(v, &WORK_DURATION_SECS))
+                .collect(),
+            ..self
+        }
+    }
 
-#[derive(Clone)]
-pub struct Stage {
-    pub duration: Duration,
-    pub is_high_intensity: bool,
-    pub label: String,
+    /// Short human readable labels for the values that are set, e.g. "45s work".
+    pub fn labels(&self) -> Vec<String> {
+        [
+            self.mode.as_ref().map(|mode| mode.label().to_string()),
+            (!self.work_ladder.is_empty()).then(|| format!("{}s work", format_work_ladder(&self.work_ladder))),
+            self.high_intensity_duration_secs.map(|v| format!("{}s work", v)),
+            self.rest_exercise_duration_secs.map(|v| format!("{}s rest", v)),
+            self.rest_set_duration_secs.map(|v| format!("{}s set break", v)),
+            self.prepare_duration_secs.map(|v| format!("{}s prepare", v)),
+            self.sets.map(|v| format!("{} sets", v)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
 }
 
-#[derive(Clone)]
-pub struct Routine {
-    pub id: String,
+/// Per-set work durations written as "20/30/40/30/20".
+pub fn format_work_ladder(ladder: &[u32]) -> String {
+    ladder.iter().join("/")
+}
+
+/// Parse per-set work durations separated by slashes, commas or spaces. Blank
+/// text is an empty ladder; anything that isn't a number is an error.
+pub fn parse_work_ladder(text: &str) -> Option<Vec<u32>> {
+    text.split(|c: char| c == '/' || c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// An exercise within a routine, optionally with its own work and rest times.
+///
+/// Entries without custom times are stored as a plain exercise name, so routines
+/// written as a list of names keep working.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "RoutineExerciseRepr", into = "RoutineExerciseRepr")]
+pub struct RoutineExercise {
     pub name: String,
-    pub exercises: Vec<String>,
+    /// Work time for this exercise instead of the routine's work duration.
+    pub duration_secs: Option<u32>,
+    /// Length of the rest that follows this exercise within a set.
+    pub rest_after_secs: Option<u32>,
+    /// Rep target for each EMOM interval of this exercise.
+    pub reps: Option<u32>,
+    /// As many rounds as possible: rounds are counted by tapping during the stage.
+    pub amrap: bool,
 }
 
-impl Routine {
-    pub fn description(&self) -> String {
-        self.exercises.join(", ")
-    }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum RoutineExerciseRepr {
+    Name(String),
+    Detailed {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_secs: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rest_after_secs: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reps: Option<u32>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        amrap: bool,
+    },
+}
 
-    pub fn duration(&self) -> Duration {
-        self.stages().iter().map(|stage| stage.duration).sum()
+impl From<RoutineExerciseRepr> for RoutineExercise {
+    fn from(repr: RoutineExerciseRepr) -> Self {
+        match repr {
+            RoutineExerciseRepr::Name(name) => name.into(),
+            RoutineExerciseRepr::Detailed {
+                name,
+                duration_secs,
+                rest_after_secs,
+                reps,
+                amrap,
+            } => RoutineExercise {
+                name,
+                duration_secs,
+                rest_after_secs,
+                reps,
+                amrap,
+            },
+        }
     }
+}
 
-    #[allow(unstable_name_collisions)]
-    pub fn stages(&self) -> Vec<Stage> {
-        // Get global settings
-        let SettingsContext { settings, .. } = expect_context::<SettingsContext>();
+impl From<RoutineExercise> for RoutineExerciseRepr {
+    fn from(exercise: RoutineExercise) -> Self {
+        match exercise {
+            RoutineExercise {
+                name,
+                duration_secs: None,
+                rest_after_secs: None,
+                reps: None,
+                amrap: false,
+            } => RoutineExerciseRepr::Name(name),
+            RoutineExercise {
+                name,
+                duration_secs,
+                rest_after_secs,
+                reps,
+                amrap,
+            } => RoutineExerciseRepr::Detailed {
+                name,
+                duration_secs,
+                rest_after_secs,
+                reps,
+                amrap,
+            },
+        }
+    }
+}
 
-        // Prepare stage
-        let prepare_stage = Stage {
-            duration: Duration::from_secs(10),
-            is_high_intensity: false,
-            label: "Prepare".to_string(),
-        };
+impl RoutineExercise {
+    pub fn clamped(self) -> RoutineExercise {
+        RoutineExercise {
+            duration_secs: self.duration_secs.map(|v| clamp(v, &WORK_DURATION_SECS)),
+            rest_after_secs: self.rest_after_secs.map(|v| clamp(v, &REST_DURATION_SECS)),
+            reps: self.reps.map(|v| clamp(v, &REPS)),
+            ..self
+        }
+    }
+}
 
-        // Create iterator of exercise stages
-        let exercise_stages = self.exercises.iter().map(|exercise| Stage {
-            duration: Duration::from_secs(settings.get().high_intensity_duration_secs as u64),
-            is_high_intensity: true,
-            label: exercise.clone(),
-        });
-
-        // Create rest stage
-        let rest_stage = Stage {
-            duration: Duration::from_secs(settings.get().rest_exercise_duration_secs as u64),
-            is_high_intensity: false,
-            label: "Rest".to_string(),
-        };
+impl From<String> for RoutineExercise {
+    fn from(name: String) -> Self {
+        RoutineExercise {
+            name,
+            ..Default::default()
+        }
+    }
+}
 
-        // Create set break stage
-        let set_break_stage = Stage {
-            duration: Duration::from_secs(settings.get().rest_set_duration_secs as u64),
-            is_high_intensity: false,
-            label: "Set Break".to_string(),
-        };
+impl From<&str> for RoutineExercise {
+    fn from(name: &str) -> Self {
+        name.to_string().into()
+    }
+}
 
-        // Create a single set of exercise stages with rests
-        let single_set: Vec<Stage> = exercise_stages.intersperse(rest_stage).collect();
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Routine {
+    pub id: String,
+    pub name: String,
+    pub exercises: Vec<RoutineExercise>,
+    #[serde(default, skip_serializing_if = "TimingOverrides::is_empty")]
+    pub overrides: TimingOverrides,
+}
 
-        // Create iterator of sets and intersperse set breaks
-        let all_stages = std::iter::repeat(single_set)
-            .take(settings.get().sets as usize)
-            .intersperse(vec![set_break_stage])
-            .flatten();
+impl Routine {
+    pub fn description(&self) -> String {
+        self.exercises.iter().map(|e| e.name.as_str()).join(", ")
+    }
 
-        // Combine prepare stage with the rest of the stages
-        std::iter::once(prepare_stage).chain(all_stages).collect()
+    /// The routine's own overrides, with any overrides from settings taking precedence.
+    pub fn effective_overrides(&self, settings: &WorkoutSettings) -> TimingOverrides {
+        match settings.routine_overrides.get(&self.id) {
+            Some(overrides) => overrides.or(&self.overrides),
+            None => self.overrides.clone(),
+        }
     }
 
-    pub fn stage_at_t(&self, t: f64) -> Option<(usize, Stage, Option<Stage>, f64)> {
-        let stages = self.stages();
-        let mut cumulative_duration = 0.0;
-
-        for i in 0..stages.len() {
-            let current_stage = &stages[i];
-            let stage_start = cumulative_duration;
-            cumulative_duration += current_stage.duration.as_secs_f64();
-
-            if t < cumulative_duration {
-                let next_stage = if i + 1 < stages.len() {
-                    Some(stages[i + 1].clone())
-                } else {
-                    None
-                };
-                let time_in_stage = t - stage_start;
-                return Some((i, current_stage.clone(), next_stage, time_in_stage));
-            }
+    /// The same routine with its exercise times and overrides inside the allowed ranges.
+    pub fn clamped(self) -> Routine {
+        Routine {
+            exercises: self.exercises.into_iter().map(RoutineExercise::clamped).collect(),
+            overrides: self.overrides.clamped(),
+            ..self
         }
+    }
 
-        None
+    /// Whether this routine was created in the routine builder rather than built in.
+    pub fn is_custom(&self) -> bool {
+        self.id.starts_with(CUSTOM_ROUTINE_PREFIX)
     }
 }
 
+pub const CUSTOM_ROUTINE_PREFIX: &str = "custom-";
+
 fn format_last_completion(last_completion: Option<DateTime<Utc>>, current_time: DateTime<chrono::Local>) -> String {
-    if let Some(completion_time) = last_completion {
-        // Convert UTC time to local time
-        let local_completion = completion_time.with_timezone(current_time.offset());
-
-        // Get the date part only (year, month, day)
-        let today = current_time.date_naive();
-        let completion_date = local_completion.date_naive();
-
-        // Calculate days between dates
-        let days_diff = today.signed_duration_since(completion_date).num_days();
-
-        match days_diff {
-            0 => "done today".to_string(),
-            1 => "done yesterday".to_string(),
-            n if n > 1 => format!("done {} days ago", n),
-            _ => "".to_string(), // Handles future dates, though this shouldn't happen
-        }
-    } else {
-        "".to_string()
+    match last_completion.map(|completion_time| format_days_ago(completion_time, current_time)) {
+        Some(days_ago) if !days_ago.is_empty() => format!("done {}", days_ago),
+        _ => "".to_string(),
+    }
+}
+
+/// "today", "yesterday" or "N days ago" in the local calendar; empty for future dates.
+pub fn format_days_ago(time: DateTime<Utc>, current_time: DateTime<chrono::Local>) -> String {
+    // Convert UTC time to local time
+    let local_time = time.with_timezone(current_time.offset());
+
+    // Get the date part only (year, month, day)
+    let today = current_time.date_naive();
+    let date = local_time.date_naive();
+
+    // Calculate days between dates
+    let days_diff = today.signed_duration_since(date).num_days();
+
+    match days_diff {
+        0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        n if n > 1 => format!("{} days ago", n),
+        _ => "".to_string(), // Handles future dates, though this shouldn't happen
     }
 }
 
 // Add this helper function above the RoutineCard component
-fn format_duration(duration: Duration) -> String {
+pub fn format_duration(duration: Duration) -> String {
     let total_seconds = duration.as_secs();
     let minutes = total_seconds / 60;
     let seconds = total_seconds % 60;
@@ -142,13 +297,7 @@ pub fn RoutineCard(
     };
 
     let SettingsContext { settings, .. } = expect_context::<SettingsContext>();
-    let last_completion = move || {
-        settings
-            .get()
-            .routine_completions
-            .get(&routine.get_value().name)
-            .cloned()
-    };
+    let last_completion = move || settings.with(|s| history::last_completion(&s.history, &routine.read_value().id));
 
     // Get current time once for the component
     let current_time = chrono::Local::now();
@@ -158,14 +307,37 @@ pub fn RoutineCard(
         class="overflow-hidden h-full bg-white rounded-lg shadow-md transition-shadow transition-transform duration-300 duration-300 transform cursor-pointer hover:shadow-lg hover:scale-102"
         on:click=handle_click
       >
-        <div class="py-3 px-4 bg-gradient-to-r from-blue-500 to-indigo-600">
+        <div class="flex justify-between items-center py-3 px-4 bg-gradient-to-r from-blue-500 to-indigo-600">
           <h3 class="text-xl font-bold text-white">{routine.get_value().name.clone()}</h3>
+          {routine
+            .get_value()
+            .is_custom()
+            .then(|| {
+              view! {
+                <a
+                  href=format!("/routines/{}/edit", routine.get_value().id)
+                  class="text-sm text-blue-100 hover:text-white"
+                  on:click=|ev| ev.stop_propagation()
+                >
+                  Edit
+                </a>
+              }
+            })}
         </div>
         <div class="p-4">
           <p class="mb-3 text-gray-700">{routine.get_value().description()}</p>
+          {move || {
+            let labels = settings.with(|s| routine.read_value().effective_overrides(s).labels());
+            (!labels.is_empty())
+              .then(|| {
+                view! { <p class="mb-3 text-xs text-gray-500">{labels.join(" · ")}</p> }
+              })
+          }}
           <div class="flex justify-between items-center">
             <span class="py-0.5 px-2.5 text-xs font-semibold text-blue-800 bg-blue-100 rounded">
-              {move || format_duration(routine.get_value().duration())}
+              {move || {
+                settings.with(|s| format_duration(Schedule::new(&routine.read_value(), s).duration()))
+              }}
             </span>
             {move || {
               if let Some(completion_time) = last_completion() {
@@ -292,6 +464,67 @@ mod tests {
         assert_eq!(result, "done yesterday");
     }
 
+    #[test]
+    fn test_deserialize_exercise_names() {
+        // Routines written as a plain list of names still load
+        let json = r#"{"id": "custom-1", "name": "Core", "exercises": ["Low plank", "Crunches"]}"#;
+        let routine: Routine = serde_json::from_str(json).expect("Failed to deserialize");
+        assert_eq!(routine.exercises, vec!["Low plank".into(), "Crunches".into()]);
+        assert_eq!(routine.description(), "Low plank, Crunches");
+    }
+
+    #[test]
+    fn test_exercise_durations_roundtrip() {
+        let routine = Routine {
+            id: "custom-1".to_string(),
+            name: "Core".to_string(),
+            exercises: vec![
+                RoutineExercise {
+                    name: "Low plank".to_string(),
+                    duration_secs: Some(60),
+                    rest_after_secs: Some(20),
+                    reps: None,
+                    amrap: false,
+                },
+                "Crunches".into(),
+                RoutineExercise {
+                    reps: Some(12),
+                    .."Burpees".into()
+                },
+                RoutineExercise {
+                    amrap: true,
+                    .."Squats".into()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(&routine).expect("Failed to serialize");
+        assert_eq!(json["exercises"][0]["duration_secs"], 60);
+        assert_eq!(json["exercises"][1], "Crunches");
+        assert_eq!(json["exercises"][2]["reps"], 12);
+        assert_eq!(json["exercises"][3]["amrap"], true);
+        assert!(json["exercises"][2].get("amrap").is_none());
+
+        let deserialized: Routine = serde_json::from_value(json).expect("Failed to deserialize");
+        assert_eq!(deserialized, routine);
+    }
+
+    #[test]
+    fn test_work_ladder_text() {
+        assert_eq!(parse_work_ladder("20/30/40/30/20"), Some(vec![20, 30, 40, 30, 20]));
+        assert_eq!(parse_work_ladder(" 20, 30 40 "), Some(vec![20, 30, 40]));
+        assert_eq!(parse_work_ladder(""), Some(vec![]));
+        assert_eq!(parse_work_ladder("20/thirty"), None);
+        assert_eq!(format_work_ladder(&[20, 30, 20]), "20/30/20");
+
+        let overrides = TimingOverrides {
+            work_ladder: vec![20, 30, 20],
+            ..Default::default()
+        };
+        assert_eq!(overrides.labels(), ["20/30/20s work"]);
+    }
+
     use quickcheck::{Arbitrary, Gen, TestResult};
     use quickcheck_macros::quickcheck;
 
@@ -0,0 +1,382 @@
+use crate::components::exercises::{Exercise, EXERCISES};
+use crate::components::routine_card::{
+    format_work_ladder, parse_work_ladder, Routine, RoutineExercise, TimingOverrides, CUSTOM_ROUTINE_PREFIX,
+};
+use crate::components::settings::{SettingsContext, WorkoutSettings, REPS, REST_DURATION_SECS, WORK_DURATION_SECS};
+use crate::workout::schedule::WorkoutMode;
+use chrono::Utc;
+use leptos::prelude::*;
+use leptos_router::hooks::{use_navigate, use_params_map};
+use std::collections::BTreeMap;
+
+/// Page for creating a new custom routine (`/routines/new`) or editing an
+/// existing one (`/routines/:id/edit`).
+#[component]
+pub fn RoutineEditorPage() -> impl IntoView {
+    let SettingsContext {
+        settings,
+        update_settings,
+        ..
+    } = expect_context::<SettingsContext>();
+
+    let params = use_params_map();
+    // Only custom routines can be edited; built-in ids are never overwritten
+    let editing_id = params
+        .with_untracked(|p| p.get("id"))
+        .filter(|id| id.starts_with(CUSTOM_ROUTINE_PREFIX));
+    let is_editing = editing_id.is_some();
+
+    let name = RwSignal::new(String::new());
+    let exercises = RwSignal::new(Vec::<RoutineExercise>::new());
+    let mode = RwSignal::new(WorkoutMode::default());
+    let work_ladder = RwSignal::new(String::new());
+
+    // Custom routines live in settings, which are loaded on the client side.
+    // Fill in the form once the routine being edited shows up.
+    let existing = {
+        let editing_id = editing_id.clone();
+        Memo::new(move |_| {
+            let id = editing_id.as_ref()?;
+            settings.with(|s| s.custom_routines.iter().find(|r| &r.id == id).cloned())
+        })
+    };
+    Effect::new(move |loaded: Option<bool>| {
+        if loaded == Some(true) {
+            return true;
+        }
+        match existing.get() {
+            Some(routine) => {
+                name.set(routine.name);
+                exercises.set(routine.exercises);
+                mode.set(routine.overrides.mode.unwrap_or_default());
+                work_ladder.set(format_work_ladder(&routine.overrides.work_ladder));
+                true
+            }
+            None => false,
+        }
+    });
+
+    let exercises_by_group =
+        EXERCISES
+            .iter()
+            .fold(BTreeMap::<&'static str, Vec<&Exercise>>::new(), |mut acc, exercise| {
+                acc.entry(exercise.group).or_default().push(exercise);
+                acc
+            });
+
+    let move_exercise = move |index: usize, offset: isize| {
+        exercises.update(|list| {
+            let target = index as isize + offset;
+            if target >= 0 && (target as usize) < list.len() {
+                list.swap(index, target as usize);
+            }
+        });
+    };
+
+    // Blank inputs mean the exercise uses the routine's timing
+    let set_exercise_time = move |index: usize, value: String, set: fn(&mut RoutineExercise, Option<u32>)| {
+        exercises.update(|list| {
+            if let Some(exercise) = list.get_mut(index) {
+                set(exercise, value.trim().parse::<u32>().ok());
+            }
+        });
+    };
+
+    // Only standard routines have a ladder; other modes ignore whatever was typed
+    let parsed_work_ladder = move || match mode.get() {
+        WorkoutMode::Standard => parse_work_ladder(&work_ladder.read()),
+        _ => Some(Vec::new()),
+    };
+
+    let can_save =
+        move || !name.read().trim().is_empty() && !exercises.read().is_empty() && parsed_work_ladder().is_some();
+
+    let navigate = use_navigate();
+    let save = {
+        let navigate = navigate.clone();
+        let editing_id = editing_id.clone();
+        move |_| {
+            if !can_save() {
+                return;
+            }
+            let existing = existing.get_untracked().unwrap_or_default();
+            let mode = mode.get();
+            let routine = Routine {
+                id: editing_id
+                    .clone()
+                    .unwrap_or_else(|| format!("{}{}", CUSTOM_ROUTINE_PREFIX, Utc::now().timestamp_millis())),
+                name: name.get().trim().to_string(),
+                exercises: exercises.get(),
+                overrides: TimingOverrides {
+                    mode: (mode != WorkoutMode::Standard).then_some(mode),
+                    work_ladder: parsed_work_ladder().unwrap_or_default(),
+                    ..existing.overrides
+                },
+            };
+            let mut custom_routines = settings.get().custom_routines;
+            match custom_routines.iter_mut().find(|r| r.id == routine.id) {
+                Some(existing) => *existing = routine,
+                None => custom_routines.push(routine),
+            }
+            update_settings.run(WorkoutSettings {
+                custom_routines,
+                ..settings.get()
+            });
+            navigate("/", Default::default());
+        }
+    };
+
+    let delete = move |_| {
+        if let Some(id) = &editing_id {
+            let mut custom_routines = settings.get().custom_routines;
+            custom_routines.retain(|r| &r.id != id);
+            update_settings.run(WorkoutSettings {
+                custom_routines,
+                ..settings.get()
+            });
+        }
+        navigate("/", Default::default());
+    };
+
+    view! {
+      <div class="container py-8 px-4 mx-auto max-w-2xl">
+        <h1 class="mb-6 text-2xl font-bold text-center text-gray-800 md:text-3xl">
+          {if is_editing { "Edit Routine" } else { "New Routine" }}
+        </h1>
+
+        <div class="p-6 mb-6 bg-white rounded-lg shadow-md">
+          <label for="routine-name" class="block mb-2 text-sm font-medium text-gray-700">
+            Name
+          </label>
+          <input
+            id="routine-name"
+            type="text"
+            class="block p-2 mb-6 w-full rounded-md border border-gray-300 focus:border-blue-500 focus:ring-blue-500"
+            prop:value=move || name.get()
+            on:input=move |ev| name.set(event_target_value(&ev))
+          />
+
+          <label for="routine-mode" class="block mb-2 text-sm font-medium text-gray-700">
+            Mode
+          </label>
+          <select
+            id="routine-mode"
+            class="block p-2 mb-6 w-full rounded-md border border-gray-300 focus:border-blue-500 focus:ring-blue-500"
+            on:change=move |ev| {
+              if let Some(new_mode) = WorkoutMode::from_key(&event_target_value(&ev)) {
+                mode.set(new_mode);
+              }
+            }
+          >
+            {WorkoutMode::ALL
+              .into_iter()
+              .map(|option| {
+                let key = option.key();
+                view! {
+                  <option value=key selected=move || mode.read().key() == key>
+                    {option.label()}
+                  </option>
+                }
+              })
+              .collect::<Vec<_>>()}
+          </select>
+
+          <div class:hidden=move || mode.get() != WorkoutMode::Standard>
+            <label for="routine-work-ladder" class="block mb-2 text-sm font-medium text-gray-700">
+              Work per set (s)
+            </label>
+            <input
+              id="routine-work-ladder"
+              type="text"
+              placeholder="e.g. 20/30/40/30/20"
+              class="block p-2 w-full rounded-md border border-gray-300 focus:border-blue-500 focus:ring-blue-500"
+              prop:value=move || work_ladder.get()
+              on:input=move |ev| work_ladder.set(event_target_value(&ev))
+            />
+            <p class=move || {
+              if parsed_work_ladder().is_some() {
+                "mt-1 mb-6 text-xs text-gray-500"
+              } else {
+                "mt-1 mb-6 text-xs text-red-600"
+              }
+            }>
+              "For a pyramid or ladder, one work duration per set. Leave blank to use the same time every set."
+            </p>
+          </div>
+
+          <h2 class="mb-2 text-sm font-medium text-gray-700">Exercises</h2>
+          <ol class="mb-6 space-y-2">
+            {move || {
+              let count = exercises.read().len();
+              exercises
+                .get()
+                .into_iter()
+                .enumerate()
+                .map(|(index, exercise)| {
+                  view! {
+                    <li class="py-2 px-3 bg-gray-50 rounded-md">
+                      <div class="flex justify-between items-center">
+                        <span class="text-gray-700">{format!("{}. {}", index + 1, exercise.name)}</span>
+                        <div class="flex space-x-2 text-sm">
+                          <button
+                            class="text-blue-600 hover:text-blue-800 disabled:opacity-30"
+                            disabled=index == 0
+                            on:click=move |_| move_exercise(index, -1)
+                          >
+                            "Up"
+                          </button>
+                          <button
+                            class="text-blue-600 hover:text-blue-800 disabled:opacity-30"
+                            disabled=index + 1 == count
+                            on:click=move |_| move_exercise(index, 1)
+                          >
+                            "Down"
+                          </button>
+                          <button
+                            class="text-red-600 hover:text-red-800"
+                            on:click=move |_| {
+                              exercises
+                                .update(|list| {
+                                  list.remove(index);
+                                })
+                            }
+                          >
+                            "Remove"
+                          </button>
+                        </div>
+                      </div>
+                      // Tabata and EMOM use fixed timings, so per-exercise times don't apply
+                      <div
+                        class="flex mt-2 space-x-4 text-xs text-gray-500"
+                        class:hidden=move || mode.get() != WorkoutMode::Standard
+                      >
+                        <label class="flex items-center">
+                          "Work (s)"
+                          <input
+                            type="number"
+                            min=WORK_DURATION_SECS.start().to_string()
+                            max=WORK_DURATION_SECS.end().to_string()
+                            placeholder="default"
+                            class="p-1 ml-2 w-20 rounded border border-gray-300"
+                            prop:value=exercise.duration_secs.map(|v| v.to_string()).unwrap_or_default()
+                            on:change=move |ev| {
+                              set_exercise_time(index, event_target_value(&ev), |e, v| e.duration_secs = v)
+                            }
+                          />
+                        </label>
+                        <label class="flex items-center">
+                          "Rest after (s)"
+                          <input
+                            type="number"
+                            min=REST_DURATION_SECS.start().to_string()
+                            max=REST_DURATION_SECS.end().to_string()
+                            placeholder="default"
+                            class="p-1 ml-2 w-20 rounded border border-gray-300"
+                            prop:value=exercise.rest_after_secs.map(|v| v.to_string()).unwrap_or_default()
+                            on:change=move |ev| {
+                              set_exercise_time(index, event_target_value(&ev), |e, v| e.rest_after_secs = v)
+                            }
+                          />
+                        </label>
+                      </div>
+                      // Logging rounds works the same whatever the mode
+                      <div class="flex mt-2 space-x-4 text-xs text-gray-500">
+                        <label class="flex items-center">
+                          <input
+                            type="checkbox"
+                            class="mr-1"
+                            prop:checked=exercise.amrap
+                            on:change=move |ev| {
+                              let amrap = event_target_checked(&ev);
+                              exercises
+                                .update(|list| {
+                                  if let Some(exercise) = list.get_mut(index) {
+                                    exercise.amrap = amrap;
+                                  }
+                                })
+                            }
+                          />
+                          "AMRAP"
+                        </label>
+                      </div>
+                      <div
+                        class="flex mt-2 space-x-4 text-xs text-gray-500"
+                        class:hidden=move || mode.get() != WorkoutMode::Emom
+                      >
+                        <label class="flex items-center">
+                          "Reps per minute"
+                          <input
+                            type="number"
+                            min=REPS.start().to_string()
+                            max=REPS.end().to_string()
+                            placeholder="none"
+                            class="p-1 ml-2 w-20 rounded border border-gray-300"
+                            prop:value=exercise.reps.map(|v| v.to_string()).unwrap_or_default()
+                            on:change=move |ev| set_exercise_time(index, event_target_value(&ev), |e, v| e.reps = v)
+                          />
+                        </label>
+                      </div>
+                    </li>
+                  }
+                })
+                .collect::<Vec<_>>()
+            }}
+          </ol>
+
+          <h2 class="mb-2 text-sm font-medium text-gray-700">Add Exercise</h2>
+          {exercises_by_group
+            .into_iter()
+            .map(|(group_name, group)| {
+              view! {
+                <div class="mb-4">
+                  <h3 class="mb-2 text-xs font-semibold text-gray-500 uppercase">{group_name}</h3>
+                  <div class="flex flex-wrap gap-2">
+                    {group
+                      .into_iter()
+                      .map(|exercise| {
+                        view! {
+                          <button
+                            class="py-1 px-3 text-sm text-blue-800 bg-blue-100 rounded-full transition-colors hover:bg-blue-200"
+                            on:click=move |_| exercises.update(|list| list.push(exercise.name.into()))
+                          >
+                            {exercise.name}
+                          </button>
+                        }
+                      })
+                      .collect::<Vec<_>>()}
+                  </div>
+                </div>
+              }
+            })
+            .collect::<Vec<_>>()}
+        </div>
+
+        <div class="flex justify-center space-x-4">
+          <a
+            href="/"
+            class="inline-block py-2 px-4 font-semibold text-gray-700 bg-white rounded-lg border border-gray-300 transition-colors hover:bg-gray-50"
+          >
+            Cancel
+          </a>
+          {is_editing
+            .then(|| {
+              view! {
+                <button
+                  class="py-2 px-4 font-semibold text-white bg-red-500 rounded-lg transition-colors hover:bg-red-600"
+                  on:click=delete
+                >
+                  Delete
+                </button>
+              }
+            })}
+          <button
+            class="py-2 px-4 font-semibold text-white bg-blue-600 rounded-lg transition-colors hover:bg-blue-700 disabled:opacity-50"
+            disabled=move || !can_save()
+            on:click=save
+          >
+            Save
+          </button>
+        </div>
+      </div>
+    }
+}
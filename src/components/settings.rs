@@ -1,6 +1,107 @@
-use chrono::{DateTime, Utc};
 use leptos::prelude::*;
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use super::backup_card::BackupCard;
+use super::routine_card::{format_work_ladder, parse_work_ladder, Routine, TimingOverrides};
+use crate::data::migrations::{migrate_settings, SETTINGS_VERSION};
+use crate::data::routines::all_routines;
+use crate::workout::history::WorkoutRecord;
+use crate::workout::schedule::{WorkoutMode, DEFAULT_PREPARE_DURATION_SECS};
+
+// Allowed ranges, shared by the sliders and by validation of loaded settings
+pub const WORK_DURATION_SECS: RangeInclusive<u32> = 5..=300;
+pub const REST_DURATION_SECS: RangeInclusive<u32> = 0..=120;
+pub const PREPARE_DURATION_SECS: RangeInclusive<u32> = 0..=60;
+pub const SETS: RangeInclusive<u32> = 1..=30;
+pub const REPS: RangeInclusive<u32> = 1..=100;
+pub const WEEKLY_GOAL: RangeInclusive<u32> = 1..=14;
+pub const RESUME_WINDOW_MINS: RangeInclusive<u32> = 5..=240;
+pub const PHASE_DURATION_SECS: RangeInclusive<u32> = 0..=600;
+
+pub fn clamp(value: u32, range: &RangeInclusive<u32>) -> u32 {
+    value.clamp(*range.start(), *range.end())
+}
+
+/// A named set of slider values that can be applied in one click.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub high_intensity_duration_secs: u32,
+    pub rest_exercise_duration_secs: u32,
+    pub rest_set_duration_secs: u32,
+    pub sets: u32,
+}
+
+impl Preset {
+    fn new(name: &str, high: u32, rest: u32, set_rest: u32, sets: u32) -> Self {
+        Preset {
+            name: name.to_string(),
+            high_intensity_duration_secs: high,
+            rest_exercise_duration_secs: rest,
+            rest_set_duration_secs: set_rest,
+            sets,
+        }
+    }
+
+    /// A preset holding the current slider values of `settings`.
+    pub fn from_settings(name: String, settings: &WorkoutSettings) -> Self {
+        Preset {
+            name,
+            high_intensity_duration_secs: settings.high_intensity_duration_secs,
+            rest_exercise_duration_secs: settings.rest_exercise_duration_secs,
+            rest_set_duration_secs: settings.rest_set_duration_secs,
+            sets: settings.sets,
+        }
+    }
+
+    /// Whether the sliders in `settings` are set to this preset.
+    pub fn matches(&self, settings: &WorkoutSettings) -> bool {
+        *self == Preset::from_settings(self.name.clone(), settings)
+    }
+
+    pub fn apply(&self, settings: WorkoutSettings) -> WorkoutSettings {
+        WorkoutSettings {
+            high_intensity_duration_secs: self.high_intensity_duration_secs,
+            rest_exercise_duration_secs: self.rest_exercise_duration_secs,
+            rest_set_duration_secs: self.rest_set_duration_secs,
+            sets: self.sets,
+            ..settings
+        }
+    }
+
+    pub fn clamped(self) -> Self {
+        Preset {
+            high_intensity_duration_secs: clamp(self.high_intensity_duration_secs, &WORK_DURATION_SECS),
+            rest_exercise_duration_secs: clamp(self.rest_exercise_duration_secs, &REST_DURATION_SECS),
+            rest_set_duration_secs: clamp(self.rest_set_duration_secs, &REST_DURATION_SECS),
+            sets: clamp(self.sets, &SETS),
+            ..self
+        }
+    }
+}
+
+/// Presets everyone starts with. They can be renamed or deleted like any other.
+pub fn default_presets() -> Vec<Preset> {
+    vec![
+        Preset::new("Low", 30, 15, 30, 3),
+        Preset::new("Mid", 45, 10, 15, 4),
+        Preset::new("High", 60, 0, 15, 6),
+    ]
+}
+
+/// Why `name` can't be given to the preset at `index`, or to a new one when `index` is
+/// `None`. Names must be unique, since imports match presets by name.
+pub fn preset_name_error(presets: &[Preset], name: &str, index: Option<usize>) -> Option<String> {
+    if name.is_empty() {
+        return Some("Preset name can't be empty".to_string());
+    }
+    presets
+        .iter()
+        .enumerate()
+        .any(|(i, preset)| Some(i) != index && preset.name == name)
+        .then(|| format!("A preset named \"{}\" already exists", name))
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct WorkoutSettings {
@@ -8,8 +109,27 @@ pub struct WorkoutSettings {
     pub rest_exercise_duration_secs: u32,
     pub rest_set_duration_secs: u32,
     pub sets: u32,
-    pub routine_completions: HashMap<String, DateTime<Utc>>,
+    /// Length of the "Prepare" stage unless a routine overrides it.
+    pub prepare_duration_secs: u32,
+    /// Show a large 3-2-1 over the timer before the first exercise.
+    pub countdown_overlay: bool,
+    /// Say "halfway" in the middle of long work stages.
+    pub halfway_cue: bool,
+    /// Say "ten seconds" near the end of long work stages.
+    pub ten_seconds_cue: bool,
+    /// Every workout started, oldest first.
+    pub history: Vec<WorkoutRecord>,
     pub voice: String,
+    pub resume_window_mins: u32,
+    /// Finished workouts per week the user is aiming for.
+    pub weekly_goal: u32,
+    /// Length of the mobility warm-up before each workout; zero skips it.
+    pub warm_up_secs: u32,
+    /// Length of the mobility cool-down after each workout; zero skips it.
+    pub cool_down_secs: u32,
+    pub presets: Vec<Preset>,
+    pub custom_routines: Vec<Routine>,
+    pub routine_overrides: HashMap<String, TimingOverrides>,
 }
 
 impl Default for WorkoutSettings {
@@ -19,13 +139,48 @@ impl Default for WorkoutSettings {
             rest_exercise_duration_secs: 15,
             rest_set_duration_secs: 30,
             sets: 3,
-            routine_completions: HashMap::new(),
+            prepare_duration_secs: DEFAULT_PREPARE_DURATION_SECS,
+            countdown_overlay: false,
+            halfway_cue: false,
+            ten_seconds_cue: false,
+            history: Vec::new(),
             voice: "freya".to_string(),
+            resume_window_mins: 60,
+            weekly_goal: 3,
+            warm_up_secs: 0,
+            cool_down_secs: 0,
+            presets: default_presets(),
+            custom_routines: Vec::new(),
+            routine_overrides: HashMap::new(),
         }
     }
 }
 
 impl WorkoutSettings {
+    /// Pull every timing value, including those of custom routines and
+    /// overrides, into the range its slider allows.
+    pub fn clamped(self) -> Self {
+        WorkoutSettings {
+            high_intensity_duration_secs: clamp(self.high_intensity_duration_secs, &WORK_DURATION_SECS),
+            rest_exercise_duration_secs: clamp(self.rest_exercise_duration_secs, &REST_DURATION_SECS),
+            rest_set_duration_secs: clamp(self.rest_set_duration_secs, &REST_DURATION_SECS),
+            sets: clamp(self.sets, &SETS),
+            prepare_duration_secs: clamp(self.prepare_duration_secs, &PREPARE_DURATION_SECS),
+            resume_window_mins: clamp(self.resume_window_mins, &RESUME_WINDOW_MINS),
+            weekly_goal: clamp(self.weekly_goal, &WEEKLY_GOAL),
+            warm_up_secs: clamp(self.warm_up_secs, &PHASE_DURATION_SECS),
+            cool_down_secs: clamp(self.cool_down_secs, &PHASE_DURATION_SECS),
+            presets: self.presets.into_iter().map(Preset::clamped).collect(),
+            custom_routines: self.custom_routines.into_iter().map(Routine::clamped).collect(),
+            routine_overrides: self
+                .routine_overrides
+                .into_iter()
+                .map(|(id, overrides)| (id, overrides.clamped()))
+                .collect(),
+            ..self
+        }
+    }
+
     fn save_to_storage(&self) -> bool {
         if let Some(window) = web_sys::window() {
             if let Ok(Some(storage)) = window.local_storage() {
@@ -45,13 +200,25 @@ impl serde::Serialize for WorkoutSettings {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("WorkoutSettings", 6)?;
+        let mut state = serializer.serialize_struct("WorkoutSettings", 18)?;
+        state.serialize_field("version", &SETTINGS_VERSION)?;
         state.serialize_field("high_intensity_duration_secs", &self.high_intensity_duration_secs)?;
         state.serialize_field("rest_exercise_duration_secs", &self.rest_exercise_duration_secs)?;
         state.serialize_field("rest_set_duration_secs", &self.rest_set_duration_secs)?;
         state.serialize_field("sets", &self.sets)?;
-        state.serialize_field("routine_completions", &self.routine_completions)?;
+        state.serialize_field("prepare_duration_secs", &self.prepare_duration_secs)?;
+        state.serialize_field("countdown_overlay", &self.countdown_overlay)?;
+        state.serialize_field("halfway_cue", &self.halfway_cue)?;
+        state.serialize_field("ten_seconds_cue", &self.ten_seconds_cue)?;
+        state.serialize_field("history", &self.history)?;
         state.serialize_field("voice", &self.voice)?;
+        state.serialize_field("resume_window_mins", &self.resume_window_mins)?;
+        state.serialize_field("weekly_goal", &self.weekly_goal)?;
+        state.serialize_field("warm_up_secs", &self.warm_up_secs)?;
+        state.serialize_field("cool_down_secs", &self.cool_down_secs)?;
+        state.serialize_field("presets", &self.presets)?;
+        state.serialize_field("custom_routines", &self.custom_routines)?;
+        state.serialize_field("routine_overrides", &self.routine_overrides)?;
         state.end()
     }
 }
@@ -73,10 +240,32 @@ impl<'de> serde::Deserialize<'de> for WorkoutSettings {
             rest_set_duration_secs: u32,
             #[serde(default = "default_sets")]
             sets: u32,
+            #[serde(default = "default_prepare_duration")]
+            prepare_duration_secs: u32,
+            #[serde(default)]
+            countdown_overlay: bool,
+            #[serde(default)]
+            halfway_cue: bool,
+            #[serde(default)]
+            ten_seconds_cue: bool,
             #[serde(default)]
-            routine_completions: HashMap<String, DateTime<Utc>>,
+            history: Vec<WorkoutRecord>,
             #[serde(default = "default_voice")]
             voice: String,
+            #[serde(default = "default_resume_window")]
+            resume_window_mins: u32,
+            #[serde(default = "default_weekly_goal")]
+            weekly_goal: u32,
+            #[serde(default)]
+            warm_up_secs: u32,
+            #[serde(default)]
+            cool_down_secs: u32,
+            #[serde(default = "default_presets")]
+            presets: Vec<Preset>,
+            #[serde(default)]
+            custom_routines: Vec<Routine>,
+            #[serde(default)]
+            routine_overrides: HashMap<String, TimingOverrides>,
         }
 
         // Helper functions to provide default values
@@ -96,20 +285,47 @@ impl<'de> serde::Deserialize<'de> for WorkoutSettings {
             WorkoutSettings::default().sets
         }
 
+        fn default_prepare_duration() -> u32 {
+            WorkoutSettings::default().prepare_duration_secs
+        }
+
         fn default_voice() -> String {
             WorkoutSettings::default().voice
         }
 
-        let helper = SettingsHelper::deserialize(deserializer)?;
+        fn default_resume_window() -> u32 {
+            WorkoutSettings::default().resume_window_mins
+        }
+
+        fn default_weekly_goal() -> u32 {
+            WorkoutSettings::default().weekly_goal
+        }
+
+        // Bring older stored settings up to the current schema first
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let value = migrate_settings(value).map_err(serde::de::Error::custom)?;
+        let helper = SettingsHelper::deserialize(value).map_err(serde::de::Error::custom)?;
 
-        Ok(WorkoutSettings {
+        let settings = WorkoutSettings {
             high_intensity_duration_secs: helper.high_intensity_duration_secs,
             rest_exercise_duration_secs: helper.rest_exercise_duration_secs,
             rest_set_duration_secs: helper.rest_set_duration_secs,
             sets: helper.sets,
-            routine_completions: helper.routine_completions,
+            prepare_duration_secs: helper.prepare_duration_secs,
+            countdown_overlay: helper.countdown_overlay,
+            halfway_cue: helper.halfway_cue,
+            ten_seconds_cue: helper.ten_seconds_cue,
+            history: helper.history,
             voice: helper.voice,
-        })
+            resume_window_mins: helper.resume_window_mins,
+            weekly_goal: helper.weekly_goal,
+            warm_up_secs: helper.warm_up_secs,
+            cool_down_secs: helper.cool_down_secs,
+            presets: helper.presets,
+            custom_routines: helper.custom_routines,
+            routine_overrides: helper.routine_overrides,
+        };
+        Ok(settings.clamped())
     }
 }
 
@@ -119,12 +335,12 @@ fn RangeSlider(
     label: String,
     value: Signal<u32>,
     on_change: Callback<u32>,
-    min: u32,
-    max: u32,
+    range: RangeInclusive<u32>,
     step: u32,
     unit: String,
 ) -> impl IntoView {
     let id = format!("slider-{}", label.to_lowercase().replace(" ", "-"));
+    let (min, max) = range.into_inner();
 
     let on_input = move |ev| {
         let new_value = event_target_value(&ev).parse::<u32>().unwrap_or_default();
@@ -158,20 +374,101 @@ fn RangeSlider(
     }
 }
 
+// Checkbox for an on/off setting
+#[component]
+fn Toggle(label: String, value: Signal<bool>, on_change: Callback<bool>) -> impl IntoView {
+    view! {
+      <label class="flex items-center mb-2 text-sm text-gray-700">
+        <input
+          type="checkbox"
+          class="mr-2 accent-blue-600"
+          prop:checked=move || value.get()
+          on:change=move |ev| on_change.run(event_target_checked(&ev))
+        />
+        {label}
+      </label>
+    }
+}
+
+// Slider for an optional per-routine value; unchecked means the fallback is used
+#[component]
+fn OverrideSlider(
+    label: String,
+    value: Signal<Option<u32>>,
+    fallback: Signal<u32>,
+    on_change: Callback<Option<u32>>,
+    range: RangeInclusive<u32>,
+    step: u32,
+    unit: String,
+) -> impl IntoView {
+    let id = format!("override-{}", label.to_lowercase().replace(" ", "-"));
+    // Only re-render the slider when the override is toggled, not while dragging
+    let enabled = Memo::new(move |_| value.get().is_some());
+
+    view! {
+      <div class="mb-2">
+        <label class="flex items-center mb-2 text-sm text-gray-700">
+          <input
+            type="checkbox"
+            id=id
+            class="mr-2 accent-blue-600"
+            prop:checked=move || enabled.get()
+            on:change=move |ev| {
+              let checked = event_target_checked(&ev);
+              on_change.run(checked.then(|| fallback.get_untracked()));
+            }
+          />
+          {format!("Override {}", label.to_lowercase())}
+        </label>
+        {move || {
+          let label = label.clone();
+          let unit = unit.clone();
+          let range = range.clone();
+          enabled
+            .get()
+            .then(move || {
+              view! {
+                <RangeSlider
+                  label=label
+                  value=Signal::derive(move || value.get().unwrap_or_else(|| fallback.get()))
+                  on_change=Callback::new(move |new_value| on_change.run(Some(new_value)))
+                  range=range
+                  step=step
+                  unit=unit
+                />
+              }
+            })
+        }}
+      </div>
+    }
+}
+
 // Create a context for the settings
 #[derive(Clone)]
 pub struct SettingsContext {
     pub settings: Signal<WorkoutSettings>,
     pub update_settings: Callback<WorkoutSettings>,
+    /// Whether the stored settings have been read, which only happens on the client.
+    pub loaded: Signal<bool>,
 }
 
+/// Where stored settings that can't be read are copied before anything replaces them.
+const UNREADABLE_SETTINGS_KEY: &str = "hiit_settings_unreadable";
+
 // Create a provider component for the settings context
 #[component]
 pub fn SettingsProvider(children: Children) -> impl IntoView {
     let settings = RwSignal::new(WorkoutSettings::default());
+    let loaded = RwSignal::new(false);
+    // Why the stored settings couldn't be read, and whether it's safe to overwrite them
+    let load_error = RwSignal::new(None::<String>);
+    let writable = StoredValue::new(true);
     let update_settings = Callback::new(move |new_settings: WorkoutSettings| {
+        let new_settings = new_settings.clamped();
         settings.set(new_settings.clone());
-        new_settings.save_to_storage();
+        if writable.get_value() {
+            new_settings.save_to_storage();
+        }
     });
 
     // Load settings from storage on the client side
@@ -179,20 +476,52 @@ pub fn SettingsProvider(children: Children) -> impl IntoView {
         if let Some(window) = web_sys::window() {
             if let Ok(Some(storage)) = window.local_storage() {
                 if let Ok(Some(json)) = storage.get_item("hiit_settings") {
-                    if let Ok(loaded_settings) = serde_json::from_str::<WorkoutSettings>(&json) {
-                        settings.set(loaded_settings);
+                    match serde_json::from_str::<WorkoutSettings>(&json) {
+                        Ok(loaded_settings) => settings.set(loaded_settings),
+                        Err(err) => {
+                            log::warn!("Unreadable settings: {}", err);
+                            // Keep the original before defaults get saved over it, or
+                            // don't save at all if it can't be kept
+                            let kept = storage.set_item(UNREADABLE_SETTINGS_KEY, &json).is_ok();
+                            writable.set_value(kept);
+                            load_error.set(Some(match kept {
+                                true => format!(
+                                    "Your saved data could not be read ({}), so defaults are in use. \
+                                     The original was kept in local storage as \"{}\".",
+                                    err, UNREADABLE_SETTINGS_KEY
+                                ),
+                                false => format!(
+                                    "Your saved data could not be read ({}), so defaults are in use. \
+                                     Changes won't be saved until it is fixed or removed.",
+                                    err
+                                ),
+                            }));
+                        }
                     }
                 }
             }
         }
+        loaded.set(true);
     });
 
     provide_context(SettingsContext {
         settings: settings.into(),
         update_settings,
+        loaded: loaded.into(),
     });
 
-    children()
+    view! {
+      {move || {
+        load_error
+          .get()
+          .map(|err| {
+            view! {
+              <div class="p-3 mx-auto mt-4 max-w-2xl text-sm text-red-800 bg-red-50 rounded-md">{err}</div>
+            }
+          })
+      }}
+      {children()}
+    }
 }
 
 #[component]
@@ -200,48 +529,79 @@ pub fn SettingsPage() -> impl IntoView {
     let SettingsContext {
         settings,
         update_settings,
+        ..
     } = expect_context::<SettingsContext>();
 
-    // Create preset settings functions
-    let apply_preset = move |high: u32, rest: u32, set_rest: u32, sets_count: u32| {
+    // Name for saving the current sliders as a new preset
+    let new_preset_name = RwSignal::new(String::new());
+    let preset_error = RwSignal::new(None::<String>);
+    let update_presets = move |change: &dyn Fn(&mut Vec<Preset>)| {
+        let mut presets = settings.get().presets;
+        change(&mut presets);
         update_settings.run(WorkoutSettings {
-            high_intensity_duration_secs: high,
-            rest_exercise_duration_secs: rest,
-            rest_set_duration_secs: set_rest,
-            sets: sets_count,
+            presets,
             ..settings.get()
         });
     };
-
-    // Functions to check if the current settings match a preset
-    let is_low_preset = move || {
-        let s = settings.get();
-        s.high_intensity_duration_secs == 30
-            && s.rest_exercise_duration_secs == 15
-            && s.rest_set_duration_secs == 30
-            && s.sets == 3
+    let save_preset = move |_| {
+        let name = new_preset_name.get().trim().to_string();
+        let error = preset_name_error(&settings.get().presets, &name, None);
+        preset_error.set(error.clone());
+        if error.is_none() {
+            let preset = Preset::from_settings(name, &settings.get());
+            update_presets(&|presets| presets.push(preset.clone()));
+            new_preset_name.set(String::new());
+        }
     };
 
-    let is_mid_preset = move || {
-        let s = settings.get();
-        s.high_intensity_duration_secs == 45
-            && s.rest_exercise_duration_secs == 10
-            && s.rest_set_duration_secs == 15
-            && s.sets == 4
+    // Routine whose overrides are being edited
+    let selected_routine = RwSignal::new(None::<String>);
+    let update_overrides = move |change: &dyn Fn(&mut TimingOverrides)| {
+        let Some(id) = selected_routine.get_untracked() else {
+            return;
+        };
+        let mut routine_overrides = settings.get().routine_overrides;
+        let overrides = routine_overrides.entry(id.clone()).or_default();
+        change(overrides);
+        if overrides.is_empty() {
+            routine_overrides.remove(&id);
+        }
+        update_settings.run(WorkoutSettings {
+            routine_overrides,
+            ..settings.get()
+        });
     };
-
-    let is_high_preset = move || {
-        let s = settings.get();
-        s.high_intensity_duration_secs == 60
-            && s.rest_exercise_duration_secs == 0
-            && s.rest_set_duration_secs == 15
-            && s.sets == 6
+    let override_field = move |get: fn(&TimingOverrides) -> Option<u32>, set: fn(&mut TimingOverrides, Option<u32>)| {
+        let value = Signal::derive(move || {
+            let id = selected_routine.get()?;
+            settings.with(|s| s.routine_overrides.get(&id).and_then(get))
+        });
+        let on_change = Callback::new(move |new_value: Option<u32>| update_overrides(&|o| set(o, new_value)));
+        (value, on_change)
     };
-
-    // Set up handlers for the preset buttons
-    let set_low_preset = move |_| apply_preset(30, 15, 30, 3);
-    let set_mid_preset = move |_| apply_preset(45, 10, 15, 4);
-    let set_high_preset = move |_| apply_preset(60, 0, 15, 6);
+    let mode_override = Signal::derive(move || {
+        let id = selected_routine.get()?;
+        settings.with(|s| s.routine_overrides.get(&id).and_then(|o| o.mode.clone()))
+    });
+    let (work_override, on_work_override) = override_field(
+        |o| o.high_intensity_duration_secs,
+        |o, v| o.high_intensity_duration_secs = v,
+    );
+    let (rest_override, on_rest_override) = override_field(
+        |o| o.rest_exercise_duration_secs,
+        |o, v| o.rest_exercise_duration_secs = v,
+    );
+    let (set_rest_override, on_set_rest_override) =
+        override_field(|o| o.rest_set_duration_secs, |o, v| o.rest_set_duration_secs = v);
+    let (prepare_override, on_prepare_override) =
+        override_field(|o| o.prepare_duration_secs, |o, v| o.prepare_duration_secs = v);
+    let (sets_override, on_sets_override) = override_field(|o| o.sets, |o, v| o.sets = v);
+    let work_ladder_override = Signal::derive(move || {
+        let id = selected_routine.get()?;
+        settings.with(|s| s.routine_overrides.get(&id).map(|o| format_work_ladder(&o.work_ladder)))
+    });
+    // Unreadable ladders aren't saved, so say so until the text is fixed
+    let work_ladder_invalid = RwSignal::new(false);
 
     view! {
       <div class="container py-8 px-4 mx-auto max-w-2xl">
@@ -252,43 +612,35 @@ pub fn SettingsPage() -> impl IntoView {
             Settings are automatically saved as you adjust them.
           </p>
 
-          <div class="flex gap-4 justify-center mb-6">
-            <button
-              class=move || {
-                if is_low_preset() {
-                  "px-4 py-2 font-medium rounded-md bg-blue-600 text-white"
-                } else {
-                  "px-4 py-2 font-medium rounded-md bg-white border border-gray-300 text-gray-700 hover:bg-gray-50"
-                }
-              }
-              on:click=set_low_preset
-            >
-              Low
-            </button>
-            <button
-              class=move || {
-                if is_mid_preset() {
-                  "px-4 py-2 font-medium rounded-md bg-blue-600 text-white"
-                } else {
-                  "px-4 py-2 font-medium rounded-md bg-white border border-gray-300 text-gray-700 hover:bg-gray-50"
-                }
-              }
-              on:click=set_mid_preset
-            >
-              Mid
-            </button>
-            <button
-              class=move || {
-                if is_high_preset() {
-                  "px-4 py-2 font-medium rounded-md bg-blue-600 text-white"
-                } else {
-                  "px-4 py-2 font-medium rounded-md bg-white border border-gray-300 text-gray-700 hover:bg-gray-50"
-                }
-              }
-              on:click=set_high_preset
-            >
-              High
-            </button>
+          <div class="flex flex-wrap gap-4 justify-center mb-6">
+            {move || {
+              settings
+                .get()
+                .presets
+                .into_iter()
+                .map(|preset| {
+                  let is_selected = {
+                    let preset = preset.clone();
+                    move || settings.with(|s| preset.matches(s))
+                  };
+                  let name = preset.name.clone();
+                  view! {
+                    <button
+                      class=move || {
+                        if is_selected() {
+                          "px-4 py-2 font-medium rounded-md bg-blue-600 text-white"
+                        } else {
+                          "px-4 py-2 font-medium rounded-md bg-white border border-gray-300 text-gray-700 hover:bg-gray-50"
+                        }
+                      }
+                      on:click=move |_| update_settings.run(preset.apply(settings.get()))
+                    >
+                      {name}
+                    </button>
+                  }
+                })
+                .collect::<Vec<_>>()
+            }}
           </div>
 
           <RangeSlider
@@ -301,8 +653,7 @@ pub fn SettingsPage() -> impl IntoView {
                   ..settings.get()
                 });
             })
-            min=5
-            max=300
+            range=WORK_DURATION_SECS
             step=5
             unit="s".to_string()
           />
@@ -317,8 +668,7 @@ pub fn SettingsPage() -> impl IntoView {
                   ..settings.get()
                 });
             })
-            min=0
-            max=120
+            range=REST_DURATION_SECS
             step=5
             unit="s".to_string()
           />
@@ -333,8 +683,7 @@ pub fn SettingsPage() -> impl IntoView {
                   ..settings.get()
                 });
             })
-            min=0
-            max=120
+            range=REST_DURATION_SECS
             step=5
             unit="s".to_string()
           />
@@ -349,12 +698,86 @@ pub fn SettingsPage() -> impl IntoView {
                   ..settings.get()
                 });
             })
-            min=1
-            max=30
+            range=SETS
             step=1
             unit="".to_string()
           />
 
+          <RangeSlider
+            label="Prepare Stage Duration".to_string()
+            value=Signal::derive(move || settings.get().prepare_duration_secs)
+            on_change=Callback::new(move |new_value| {
+              update_settings
+                .run(WorkoutSettings {
+                  prepare_duration_secs: new_value,
+                  ..settings.get()
+                });
+            })
+            range=PREPARE_DURATION_SECS
+            step=5
+            unit="s".to_string()
+          />
+
+          <RangeSlider
+            label="Warm-up".to_string()
+            value=Signal::derive(move || settings.get().warm_up_secs)
+            on_change=Callback::new(move |new_value| {
+              update_settings
+                .run(WorkoutSettings {
+                  warm_up_secs: new_value,
+                  ..settings.get()
+                });
+            })
+            range=PHASE_DURATION_SECS
+            step=30
+            unit="s".to_string()
+          />
+
+          <RangeSlider
+            label="Cool-down".to_string()
+            value=Signal::derive(move || settings.get().cool_down_secs)
+            on_change=Callback::new(move |new_value| {
+              update_settings
+                .run(WorkoutSettings {
+                  cool_down_secs: new_value,
+                  ..settings.get()
+                });
+            })
+            range=PHASE_DURATION_SECS
+            step=30
+            unit="s".to_string()
+          />
+
+          <RangeSlider
+            label="Weekly Goal".to_string()
+            value=Signal::derive(move || settings.get().weekly_goal)
+            on_change=Callback::new(move |new_value| {
+              update_settings
+                .run(WorkoutSettings {
+                  weekly_goal: new_value,
+                  ..settings.get()
+                });
+            })
+            range=WEEKLY_GOAL
+            step=1
+            unit="/week".to_string()
+          />
+
+          <RangeSlider
+            label="Resume Window".to_string()
+            value=Signal::derive(move || settings.get().resume_window_mins)
+            on_change=Callback::new(move |new_value| {
+              update_settings
+                .run(WorkoutSettings {
+                  resume_window_mins: new_value,
+                  ..settings.get()
+                });
+            })
+            range=RESUME_WINDOW_MINS
+            step=5
+            unit="min".to_string()
+          />
+
           <div class="mb-6">
             <label for="voice-select" class="block mb-2 text-sm font-medium text-gray-700">
               Voice
@@ -375,8 +798,264 @@ pub fn SettingsPage() -> impl IntoView {
               <option value="vlad" selected=move || settings.get().voice == "vlad">Vlad</option>
             </select>
           </div>
+
+          <Toggle
+            label="Show a 3-2-1 countdown before the first exercise".to_string()
+            value=Signal::derive(move || settings.get().countdown_overlay)
+            on_change=Callback::new(move |new_value| {
+              update_settings
+                .run(WorkoutSettings {
+                  countdown_overlay: new_value,
+                  ..settings.get()
+                });
+            })
+          />
+          <Toggle
+            label="Say \"halfway\" in the middle of long exercises".to_string()
+            value=Signal::derive(move || settings.get().halfway_cue)
+            on_change=Callback::new(move |new_value| {
+              update_settings
+                .run(WorkoutSettings {
+                  halfway_cue: new_value,
+                  ..settings.get()
+                });
+            })
+          />
+          <Toggle
+            label="Say \"ten seconds\" near the end of long exercises".to_string()
+            value=Signal::derive(move || settings.get().ten_seconds_cue)
+            on_change=Callback::new(move |new_value| {
+              update_settings
+                .run(WorkoutSettings {
+                  ten_seconds_cue: new_value,
+                  ..settings.get()
+                });
+            })
+          />
         </div>
 
+        <div class="p-6 mb-6 bg-white rounded-lg shadow-md">
+          <h2 class="mb-2 text-lg font-semibold text-gray-800">Presets</h2>
+          <p class="mb-4 text-sm text-gray-600">
+            Save the current work, rest and set values under a name to switch back to them later.
+          </p>
+
+          <ul class="mb-4 space-y-2">
+            {move || {
+              settings
+                .get()
+                .presets
+                .into_iter()
+                .enumerate()
+                .map(|(index, preset)| {
+                  view! {
+                    <li class="flex items-center space-x-2">
+                      <input
+                        type="text"
+                        class="flex-1 p-2 rounded-md border border-gray-300 focus:border-blue-500 focus:ring-blue-500"
+                        prop:value=preset.name.clone()
+                        on:change=move |ev| {
+                          let name = event_target_value(&ev).trim().to_string();
+                          let error = preset_name_error(&settings.get().presets, &name, Some(index));
+                          preset_error.set(error.clone());
+                          match error {
+                            None => {
+                              update_presets(&|presets| {
+                                if let Some(preset) = presets.get_mut(index) {
+                                  preset.name = name.clone();
+                                }
+                              })
+                            }
+                            // Put the old name back rather than leave the rejected one showing
+                            Some(_) => {
+                              event_target::<web_sys::HtmlInputElement>(&ev).set_value(&preset.name)
+                            }
+                          }
+                        }
+                      />
+                      <span class="text-xs text-gray-500">
+                        {format!(
+                          "{}s / {}s / {}s x{}",
+                          preset.high_intensity_duration_secs,
+                          preset.rest_exercise_duration_secs,
+                          preset.rest_set_duration_secs,
+                          preset.sets,
+                        )}
+                      </span>
+                      <button
+                        class="text-sm text-red-600 hover:text-red-800"
+                        on:click=move |_| {
+                          update_presets(&|presets| {
+                            if index < presets.len() {
+                              presets.remove(index);
+                            }
+                          })
+                        }
+                      >
+                        "Delete"
+                      </button>
+                    </li>
+                  }
+                })
+                .collect::<Vec<_>>()
+            }}
+          </ul>
+
+          <div class="flex space-x-2">
+            <input
+              id="new-preset-name"
+              type="text"
+              placeholder="Preset name"
+              class="flex-1 p-2 rounded-md border border-gray-300 focus:border-blue-500 focus:ring-blue-500"
+              prop:value=move || new_preset_name.get()
+              on:input=move |ev| {
+                new_preset_name.set(event_target_value(&ev));
+                preset_error.set(None);
+              }
+            />
+            <button
+              class="py-2 px-4 font-semibold text-white bg-blue-600 rounded-lg transition-colors hover:bg-blue-700 disabled:opacity-50"
+              disabled=move || new_preset_name.read().trim().is_empty()
+              on:click=save_preset
+            >
+              "Save current"
+            </button>
+          </div>
+          <Show when=move || preset_error.read().is_some()>
+            <p class="mt-2 text-sm text-red-600">{move || preset_error.get()}</p>
+          </Show>
+        </div>
+
+        <div class="p-6 mb-6 bg-white rounded-lg shadow-md">
+          <h2 class="mb-2 text-lg font-semibold text-gray-800">Routine Overrides</h2>
+          <p class="mb-4 text-sm text-gray-600">
+            Use different timings for a single routine. Anything not overridden follows the settings above.
+          </p>
+
+          <select
+            id="override-routine-select"
+            class="block p-2 mb-6 w-full rounded-md border border-gray-300 focus:border-blue-500 focus:ring-blue-500"
+            on:change=move |ev| {
+              let value = event_target_value(&ev);
+              selected_routine.set((!value.is_empty()).then_some(value));
+              work_ladder_invalid.set(false);
+            }
+          >
+            <option value="">Choose a routine</option>
+            {move || {
+              settings
+                .with(all_routines)
+                .into_iter()
+                .map(|routine| {
+                  let id = routine.id.clone();
+                  view! {
+                    <option value=routine.id selected=move || selected_routine.get() == Some(id.clone())>
+                      {routine.name}
+                    </option>
+                  }
+                })
+                .collect::<Vec<_>>()
+            }}
+          </select>
+
+          <Show when=move || selected_routine.get().is_some()>
+            <label for="override-mode" class="block mb-2 text-sm text-gray-700">
+              Mode
+            </label>
+            <select
+              id="override-mode"
+              class="block p-2 mb-4 w-full rounded-md border border-gray-300 focus:border-blue-500 focus:ring-blue-500"
+              on:change=move |ev| update_overrides(&|o| o.mode = WorkoutMode::from_key(&event_target_value(&ev)))
+            >
+              <option value="" selected=move || mode_override.get().is_none()>
+                "Routine default"
+              </option>
+              {WorkoutMode::ALL
+                .into_iter()
+                .map(|mode| {
+                  let key = mode.key();
+                  view! {
+                    <option
+                      value=key
+                      selected=move || mode_override.get().is_some_and(|m| m.key() == key)
+                    >
+                      {mode.label()}
+                    </option>
+                  }
+                })
+                .collect::<Vec<_>>()}
+            </select>
+            <OverrideSlider
+              label="Work Duration".to_string()
+              value=work_override
+              fallback=Signal::derive(move || settings.get().high_intensity_duration_secs)
+              on_change=on_work_override
+              range=WORK_DURATION_SECS
+              step=5
+              unit="s".to_string()
+            />
+            <OverrideSlider
+              label="Rest Duration".to_string()
+              value=rest_override
+              fallback=Signal::derive(move || settings.get().rest_exercise_duration_secs)
+              on_change=on_rest_override
+              range=REST_DURATION_SECS
+              step=5
+              unit="s".to_string()
+            />
+            <OverrideSlider
+              label="Set Break Duration".to_string()
+              value=set_rest_override
+              fallback=Signal::derive(move || settings.get().rest_set_duration_secs)
+              on_change=on_set_rest_override
+              range=REST_DURATION_SECS
+              step=5
+              unit="s".to_string()
+            />
+            <OverrideSlider
+              label="Prepare Duration".to_string()
+              value=prepare_override
+              fallback=Signal::derive(move || settings.get().prepare_duration_secs)
+              on_change=on_prepare_override
+              range=PREPARE_DURATION_SECS
+              step=5
+              unit="s".to_string()
+            />
+            <OverrideSlider
+              label="Sets".to_string()
+              value=sets_override
+              fallback=Signal::derive(move || settings.get().sets)
+              on_change=on_sets_override
+              range=SETS
+              step=1
+              unit="".to_string()
+            />
+            <label for="override-work-ladder" class="block mb-2 text-sm text-gray-700">
+              Work per set (s)
+            </label>
+            <input
+              id="override-work-ladder"
+              type="text"
+              placeholder="e.g. 20/30/40/30/20"
+              class="block p-2 w-full rounded-md border border-gray-300 focus:border-blue-500 focus:ring-blue-500"
+              prop:value=move || work_ladder_override.get().unwrap_or_default()
+              on:input=move |ev| work_ladder_invalid.set(parse_work_ladder(&event_target_value(&ev)).is_none())
+              on:change=move |ev| {
+                if let Some(ladder) = parse_work_ladder(&event_target_value(&ev)) {
+                  update_overrides(&|o| o.work_ladder = ladder.clone());
+                }
+              }
+            />
+            <p class=move || {
+              if work_ladder_invalid.get() { "mt-1 text-xs text-red-600" } else { "mt-1 text-xs text-gray-500" }
+            }>
+              "A pyramid or ladder replaces the work duration and number of sets above."
+            </p>
+          </Show>
+        </div>
+
+        <BackupCard />
+
         <div class="text-center">
           <a
             href="/"
@@ -392,7 +1071,12 @@ pub fn SettingsPage() -> impl IntoView {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::components::routine_card::RoutineExercise;
+    use crate::data::routines::get_routines;
+    use crate::workout::history::last_completion;
+    use crate::workout::schedule::Schedule;
     use chrono::{TimeZone, Utc};
+    use std::time::Duration;
 
     // Add quickcheck imports for property testing
     #[cfg(test)]
@@ -415,7 +1099,7 @@ mod tests {
         assert_eq!(settings.rest_exercise_duration_secs, 15);
         assert_eq!(settings.rest_set_duration_secs, 30);
         assert_eq!(settings.sets, 3);
-        assert!(settings.routine_completions.is_empty());
+        assert!(settings.history.is_empty());
     }
 
     #[test]
@@ -435,7 +1119,7 @@ mod tests {
         assert_eq!(settings.rest_exercise_duration_secs, 20);
         assert_eq!(settings.rest_set_duration_secs, 45);
         assert_eq!(settings.sets, 5);
-        assert!(settings.routine_completions.is_empty());
+        assert!(settings.history.is_empty());
     }
 
     #[test]
@@ -462,29 +1146,53 @@ mod tests {
         let settings: WorkoutSettings = serde_json::from_str(&json).expect("Failed to deserialize");
 
         assert_eq!(settings.high_intensity_duration_secs, 30);
-        assert_eq!(settings.routine_completions.len(), 2);
-        assert!(settings.routine_completions.contains_key("routine1"));
-        assert!(settings.routine_completions.contains_key("routine2"));
+        assert_eq!(settings.history.len(), 2);
+        assert!(settings.history.iter().all(|record| record.finished));
 
-        // Check that timestamps were correctly deserialized
-        let routine1_completion = settings.routine_completions.get("routine1").unwrap();
-        assert_eq!(*routine1_completion, timestamp);
+        // Completions are migrated into history records keyed by routine
+        assert_eq!(last_completion(&settings.history, "routine1"), Some(timestamp));
+        assert_eq!(last_completion(&settings.history, "routine2"), Some(timestamp));
+    }
+
+    #[test]
+    fn test_migrate_routine_completions_by_name() {
+        let timestamp = Utc.with_ymd_and_hms(2023, 5, 15, 10, 30, 0).unwrap();
+        let json = format!(r#"{{"routine_completions": {{"Core": "{}"}}}}"#, timestamp.to_rfc3339());
+
+        let settings: WorkoutSettings = serde_json::from_str(&json).expect("Failed to deserialize");
+
+        assert_eq!(last_completion(&settings.history, "3"), Some(timestamp));
+        assert_eq!(settings.history[0].routine_name, "Core");
     }
 
     #[test]
     fn test_serialize() {
         // Create a settings object
-        let mut routine_completions = HashMap::new();
         let timestamp = Utc.with_ymd_and_hms(2023, 5, 15, 10, 30, 0).unwrap();
-        routine_completions.insert("routine1".to_string(), timestamp);
+        let history = vec![WorkoutRecord::completed(
+            &get_routines()[0],
+            &WorkoutSettings::default(),
+            timestamp,
+        )];
 
         let settings = WorkoutSettings {
             high_intensity_duration_secs: 40,
             rest_exercise_duration_secs: 10,
             rest_set_duration_secs: 20,
             sets: 4,
-            routine_completions,
+            prepare_duration_secs: 15,
+            countdown_overlay: true,
+            halfway_cue: true,
+            ten_seconds_cue: false,
+            history,
             voice: "freya".to_string(),
+            resume_window_mins: 30,
+            weekly_goal: 4,
+            warm_up_secs: 120,
+            cool_down_secs: 0,
+            presets: default_presets(),
+            custom_routines: Vec::new(),
+            routine_overrides: HashMap::new(),
         };
 
         // Serialize to JSON
@@ -497,27 +1205,71 @@ mod tests {
         assert_eq!(parsed["rest_exercise_duration_secs"], 10);
         assert_eq!(parsed["rest_set_duration_secs"], 20);
         assert_eq!(parsed["sets"], 4);
-        assert!(parsed["routine_completions"].is_object());
-        assert!(parsed["routine_completions"]
-            .as_object()
-            .unwrap()
-            .contains_key("routine1"));
+        assert_eq!(parsed["prepare_duration_secs"], 15);
+        assert_eq!(parsed["countdown_overlay"], true);
+        assert_eq!(parsed["halfway_cue"], true);
+        assert_eq!(parsed["ten_seconds_cue"], false);
+        assert_eq!(parsed["resume_window_mins"], 30);
+        assert_eq!(parsed["weekly_goal"], 4);
+        assert_eq!(parsed["warm_up_secs"], 120);
+        assert_eq!(parsed["presets"][1]["name"], "Mid");
+        assert_eq!(parsed["version"], SETTINGS_VERSION);
+        assert!(parsed["history"].is_array());
+        assert_eq!(parsed["history"][0]["routine_id"], "2");
+        assert!(parsed.get("routine_completions").is_none());
     }
 
     #[test]
     fn test_roundtrip_serialization() {
         // Create a settings object
-        let mut routine_completions = HashMap::new();
         let timestamp = Utc.with_ymd_and_hms(2023, 5, 15, 10, 30, 0).unwrap();
-        routine_completions.insert("routine1".to_string(), timestamp);
+        let history = vec![WorkoutRecord::completed(
+            &get_routines()[0],
+            &WorkoutSettings::default(),
+            timestamp,
+        )];
 
         let original = WorkoutSettings {
             high_intensity_duration_secs: 45,
             rest_exercise_duration_secs: 15,
             rest_set_duration_secs: 30,
             sets: 5,
-            routine_completions,
+            prepare_duration_secs: 20,
+            countdown_overlay: false,
+            halfway_cue: false,
+            ten_seconds_cue: true,
+            history,
             voice: "freya".to_string(),
+            resume_window_mins: 90,
+            weekly_goal: 2,
+            warm_up_secs: 60,
+            cool_down_secs: 90,
+            presets: vec![Preset::new("Sprint", 20, 10, 60, 8)],
+            custom_routines: vec![Routine {
+                id: "custom-1".to_string(),
+                name: "Legs".to_string(),
+                exercises: vec![
+                    "Squats".into(),
+                    RoutineExercise {
+                        name: "Lunges".to_string(),
+                        duration_secs: Some(40),
+                        rest_after_secs: None,
+                        reps: Some(15),
+                        amrap: false,
+                    },
+                ],
+                overrides: TimingOverrides {
+                    sets: Some(5),
+                    ..Default::default()
+                },
+            }],
+            routine_overrides: HashMap::from([(
+                "6".to_string(),
+                TimingOverrides {
+                    high_intensity_duration_secs: Some(60),
+                    ..Default::default()
+                },
+            )]),
         };
 
         // Serialize and then deserialize
@@ -551,7 +1303,74 @@ mod tests {
             WorkoutSettings::default().rest_set_duration_secs
         );
         assert_eq!(settings.sets, WorkoutSettings::default().sets);
-        assert!(settings.routine_completions.is_empty());
+        assert!(settings.history.is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_clamps_out_of_range_values() {
+        let json = r#"{
+            "high_intensity_duration_secs": 0,
+            "rest_exercise_duration_secs": 1000,
+            "sets": 4294967295,
+            "routine_overrides": {"3": {"sets": 0, "prepare_duration_secs": 600}},
+            "custom_routines": [{"id": "custom-1", "name": "Legs", "exercises": [{"name": "Squats", "duration_secs": 1}]}]
+        }"#;
+
+        let settings: WorkoutSettings = serde_json::from_str(json).expect("Failed to deserialize");
+
+        assert_eq!(settings.high_intensity_duration_secs, 5);
+        assert_eq!(settings.rest_exercise_duration_secs, 120);
+        assert_eq!(settings.sets, 30);
+        assert_eq!(settings.routine_overrides["3"].sets, Some(1));
+        assert_eq!(settings.routine_overrides["3"].prepare_duration_secs, Some(60));
+        assert_eq!(settings.custom_routines[0].exercises[0].duration_secs, Some(5));
+    }
+
+    #[test]
+    fn test_presets() {
+        let settings = WorkoutSettings::default();
+        let presets = default_presets();
+        assert!(presets[0].matches(&settings));
+        assert!(!presets[2].matches(&settings));
+
+        let high = presets[2].apply(settings.clone());
+        assert!(presets[2].matches(&high));
+        assert_eq!(high.sets, 6);
+        assert_eq!(high.voice, settings.voice);
+
+        let saved = Preset::from_settings("Mine".to_string(), &high);
+        assert_eq!(
+            saved,
+            Preset {
+                name: "Mine".to_string(),
+                ..presets[2].clone()
+            }
+        );
+    }
+
+    #[test]
+    fn test_preset_names_are_unique() {
+        let presets = default_presets();
+        assert_eq!(preset_name_error(&presets, "Mine", None), None);
+        assert!(preset_name_error(&presets, "Mid", None).is_some());
+        assert!(preset_name_error(&presets, "", None).is_some());
+
+        // Renaming may keep the preset's own name but not take another's
+        assert_eq!(preset_name_error(&presets, "Mid", Some(1)), None);
+        assert_eq!(
+            preset_name_error(&presets, "Mid", Some(0)),
+            Some("A preset named \"Mid\" already exists".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_keeps_user_presets() {
+        // Older settings get the built-in presets; saved lists are kept as they are
+        let settings: WorkoutSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.presets, default_presets());
+
+        let settings: WorkoutSettings = serde_json::from_str(r#"{"presets": []}"#).unwrap();
+        assert!(settings.presets.is_empty());
     }
 
     #[test]
@@ -579,18 +1398,88 @@ mod tests {
             let rest_exercise = u32::arbitrary(g);
             let rest_set = u32::arbitrary(g);
             let sets = u32::arbitrary(g);
-
-            // Generate a small number of routine completions
-            let mut routine_completions = HashMap::new();
-            let count = u32::arbitrary(g) % 10; // 0-9 completions
-
-            for i in 0..count {
-                // Generate a plausible timestamp within the last year
-                let days_ago = u32::arbitrary(g) % 365;
-                let timestamp = Utc::now() - chrono::Duration::days(days_ago as i64);
-                routine_completions.insert(format!("routine{}", i), timestamp);
+            let prepare = u32::arbitrary(g);
+            let resume_window = u32::arbitrary(g);
+            let weekly_goal = u32::arbitrary(g);
+            let warm_up = u32::arbitrary(g);
+            let cool_down = u32::arbitrary(g);
+
+            // Keep some of the built-in presets and add a few of our own
+            let mut presets: Vec<Preset> = default_presets().into_iter().filter(|_| bool::arbitrary(g)).collect();
+            for _ in 0..u32::arbitrary(g) % 3 {
+                presets.push(Preset {
+                    name: String::arbitrary(g),
+                    high_intensity_duration_secs: u32::arbitrary(g),
+                    rest_exercise_duration_secs: u32::arbitrary(g),
+                    rest_set_duration_secs: u32::arbitrary(g),
+                    sets: u32::arbitrary(g),
+                });
             }
 
+            // Generate a small history of finished and abandoned workouts
+            let routines = get_routines();
+            let history = (0..u32::arbitrary(g) % 10)
+                .map(|_| {
+                    // Generate a plausible timestamp within the last year
+                    let days_ago = u32::arbitrary(g) % 365;
+                    let started_at = Utc::now() - chrono::Duration::days(days_ago as i64);
+                    let elapsed = Duration::from_secs(u64::arbitrary(g) % 1200);
+                    WorkoutRecord::new(
+                        &routines[usize::arbitrary(g) % routines.len()],
+                        &WorkoutSettings::default(),
+                        started_at,
+                        started_at + chrono::Duration::from_std(elapsed).unwrap(),
+                        elapsed,
+                    )
+                })
+                .collect();
+
+            // Generate a few custom routines from known exercise names
+            let names = ["Squats", "Lunges", "Burpees", "Low plank"];
+            let custom_routines = (0..u32::arbitrary(g) % 3)
+                .map(|i| Routine {
+                    id: format!("custom-{}", i),
+                    name: String::arbitrary(g),
+                    exercises: (0..u32::arbitrary(g) % 5)
+                        .map(|_| RoutineExercise {
+                            name: names[usize::arbitrary(g) % names.len()].to_string(),
+                            duration_secs: Option::<u32>::arbitrary(g),
+                            rest_after_secs: Option::<u32>::arbitrary(g),
+                            reps: Option::<u32>::arbitrary(g),
+                            amrap: bool::arbitrary(g),
+                        })
+                        .collect(),
+                    overrides: TimingOverrides {
+                        sets: Option::<u32>::arbitrary(g),
+                        ..Default::default()
+                    },
+                })
+                .collect();
+
+            // Override a random subset of fields for a few routines
+            let routine_overrides = (0..u32::arbitrary(g) % 3)
+                .map(|i| {
+                    let overrides = TimingOverrides {
+                        high_intensity_duration_secs: Option::<u32>::arbitrary(g),
+                        rest_exercise_duration_secs: Option::<u32>::arbitrary(g),
+                        rest_set_duration_secs: Option::<u32>::arbitrary(g),
+                        prepare_duration_secs: Option::<u32>::arbitrary(g),
+                        sets: Option::<u32>::arbitrary(g),
+                        mode: g
+                            .choose(&[
+                                None,
+                                Some(WorkoutMode::Standard),
+                                Some(WorkoutMode::Tabata),
+                                Some(WorkoutMode::Emom),
+                            ])
+                            .cloned()
+                            .flatten(),
+                        work_ladder: Vec::<u32>::arbitrary(g),
+                    };
+                    (i.to_string(), overrides)
+                })
+                .collect();
+
             // Choose a random voice from the available options
             let voices = ["freya", "vlad"];
             let voice_index = usize::arbitrary(g) % voices.len();
@@ -601,8 +1490,19 @@ mod tests {
                 rest_exercise_duration_secs: rest_exercise,
                 rest_set_duration_secs: rest_set,
                 sets,
-                routine_completions,
+                prepare_duration_secs: prepare,
+                countdown_overlay: bool::arbitrary(g),
+                halfway_cue: bool::arbitrary(g),
+                ten_seconds_cue: bool::arbitrary(g),
+                history,
                 voice,
+                resume_window_mins: resume_window,
+                weekly_goal,
+                warm_up_secs: warm_up,
+                cool_down_secs: cool_down,
+                presets,
+                custom_routines,
+                routine_overrides,
             }
         }
     }
@@ -622,7 +1522,41 @@ mod tests {
             Err(_) => return false,
         };
 
-        // Check equality directly thanks to PartialEq implementation
-        settings == deserialized
+        // Loading clamps out of range values, otherwise nothing changes
+        settings.clamped() == deserialized
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn prop_clamped_settings_stay_in_range(settings: WorkoutSettings) -> bool {
+        let clamped = settings.clamped();
+        WORK_DURATION_SECS.contains(&clamped.high_intensity_duration_secs)
+            && REST_DURATION_SECS.contains(&clamped.rest_exercise_duration_secs)
+            && REST_DURATION_SECS.contains(&clamped.rest_set_duration_secs)
+            && SETS.contains(&clamped.sets)
+            && PREPARE_DURATION_SECS.contains(&clamped.prepare_duration_secs)
+            && RESUME_WINDOW_MINS.contains(&clamped.resume_window_mins)
+            && WEEKLY_GOAL.contains(&clamped.weekly_goal)
+            && PHASE_DURATION_SECS.contains(&clamped.warm_up_secs)
+            && PHASE_DURATION_SECS.contains(&clamped.cool_down_secs)
+            && clamped.clone().clamped() == clamped
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn prop_clamped_schedules_are_sane(settings: WorkoutSettings) -> bool {
+        let settings = settings.clamped();
+        all_routines(&settings).iter().all(|routine| {
+            let schedule = Schedule::new(routine, &settings);
+            let exercises = routine.exercises.len() as u64;
+            let longest_set = exercises * (WORK_DURATION_SECS.end() + REST_DURATION_SECS.end()) as u64;
+            let limit = (longest_set + *REST_DURATION_SECS.end() as u64) * *SETS.end() as u64
+                + *PREPARE_DURATION_SECS.end() as u64
+                + 2 * *PHASE_DURATION_SECS.end() as u64;
+            schedule.duration().as_secs() <= limit
+                && schedule
+                    .stages()
+                    .iter()
+                    .filter(|stage| stage.is_high_intensity)
+                    .all(|stage| !stage.duration.is_zero())
+        })
     }
 }
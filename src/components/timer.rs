@@ -1,15 +1,24 @@
+use crate::components::routine_card::Routine;
 use crate::components::screen_wake_lock::ScreenWakeLock;
-use crate::data::routines::get_routines;
-use chrono::Utc;
+use crate::data::routines::find_routine;
+use crate::workout::clock::WorkoutClock;
+use crate::workout::cues::{countdown_digit, CueOptions, CuePlan};
+use crate::workout::history::{tap_round, StageRounds, WorkoutRecord};
+use crate::workout::schedule::Schedule;
+use crate::workout::session::{SavedSession, WorkoutSession};
+use chrono::{DateTime, Utc};
+use instant::Instant;
 use leptos::prelude::*;
-use leptos_router::hooks::use_params_map;
+use leptos_router::hooks::{use_params_map, use_query_map};
 use leptos_use::{use_interval_with_options, UseIntervalOptions, UseIntervalReturn};
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::time::Duration;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
 use web_sys::{HtmlAudioElement, SpeechSynthesisUtterance};
 
-use super::settings::SettingsContext;
+use super::settings::{SettingsContext, WorkoutSettings};
 
 // Helper function to convert announcement text to MP3 file path
 fn text_to_mp3_path(text: &str, voice: &str) -> String {
@@ -17,77 +26,106 @@ fn text_to_mp3_path(text: &str, voice: &str) -> String {
     format!("/audio/{}_{}.mp3", filename, voice.to_lowercase())
 }
 
-// Helper function to play audio with TTS fallback
-fn play_audio(text: &str, voice: &str) -> bool {
-    use std::cell::RefCell;
+/// Announcements waiting to be spoken. Clips play one at a time, each starting
+/// when the previous one has ended, so cues due together don't cut each other off.
+#[derive(Default)]
+struct AudioQueue {
+    audio: Option<HtmlAudioElement>,
+    pending: VecDeque<(String, String)>,
+    speaking: bool,
+}
 
-    // Static audio element that will be initialized once
-    thread_local! {
-        static AUDIO_ELEMENT: RefCell<Option<HtmlAudioElement>> = const { RefCell::new(None) };
-    }
+thread_local! {
+    static AUDIO_QUEUE: RefCell<AudioQueue> = RefCell::new(AudioQueue::default());
+}
 
-    fn tts_play(text: &str) -> bool {
-        if let Some(window) = web_sys::window() {
-            if let Ok(speech) = window.speech_synthesis() {
-                speech.cancel();
-                if let Ok(utterance) = SpeechSynthesisUtterance::new_with_text(text) {
-                    speech.speak(&utterance);
-                    return true;
-                }
-            }
-        }
-        false
+// Queue an announcement, playing it straight away if nothing else is
+fn play_audio(text: &str, voice: &str) {
+    let idle = AUDIO_QUEUE.with_borrow_mut(|queue| {
+        queue.pending.push_back((text.to_string(), voice.to_string()));
+        !queue.speaking
+    });
+    if idle {
+        play_next();
     }
+}
 
-    // Initialize the audio element if it hasn't been created yet
-    AUDIO_ELEMENT.with(|audio_cell| {
-        if audio_cell.borrow().is_none() {
-            if let Ok(audio) = HtmlAudioElement::new() {
-                *audio_cell.borrow_mut() = Some(audio);
-            }
-        }
-
-        // Try to play MP3 file using our static audio element
-        if let Some(audio) = audio_cell.borrow().as_ref() {
-            let mp3_path = text_to_mp3_path(text, voice);
-            audio.set_src(&mp3_path);
-
-            // Add event listener for error to provide fallback to TTS
-            let tts_text = text.to_string();
-            let error_callback = Closure::once(Box::new(move |_: web_sys::Event| {
-                // Error playing audio, fall back to TTS
-                tts_play(&tts_text);
-            }));
+// Forget announcements that haven't started yet, e.g. after skipping a stage
+fn clear_audio_queue() {
+    AUDIO_QUEUE.with_borrow_mut(|queue| queue.pending.clear());
+}
 
-            // Attempt to add the error event listener
-            let _ = audio.add_event_listener_with_callback("error", error_callback.as_ref().unchecked_ref());
+// Play the next queued announcement, if any
+fn play_next() {
+    let next = AUDIO_QUEUE.with_borrow_mut(|queue| {
+        let next = queue.pending.pop_front();
+        queue.speaking = next.is_some();
+        if next.is_some() && queue.audio.is_none() {
+            queue.audio = HtmlAudioElement::new().ok().inspect(|audio| {
+                // Registered once, as the element is reused for every clip
+                let on_ended = Closure::<dyn Fn()>::new(play_next);
+                let _ = audio.add_event_listener_with_callback("ended", on_ended.as_ref().unchecked_ref());
+                on_ended.forget();
+            });
+        }
+        next.map(|next| (next, queue.audio.clone()))
+    });
+    let Some(((text, voice), audio)) = next else {
+        return;
+    };
 
-            // Try to play the audio
-            if audio.play().is_err() {
-                return tts_play(text);
+    let Some(audio) = audio else {
+        return tts_play(&text);
+    };
+    audio.set_src(&text_to_mp3_path(&text, &voice));
+    match audio.play() {
+        // A missing clip or a blocked autoplay rejects the promise instead of ending
+        Ok(promise) => wasm_bindgen_futures::spawn_local(async move {
+            if JsFuture::from(promise).await.is_err() {
+                tts_play(&text);
             }
+        }),
+        Err(_) => tts_play(&text),
+    }
+}
 
-            // Assuming it starts playing until we get an error event
-            return true;
+// Speak an announcement whose clip can't be played, then carry on with the queue
+fn tts_play(text: &str) {
+    let speech = web_sys::window().and_then(|window| window.speech_synthesis().ok());
+    match speech.zip(SpeechSynthesisUtterance::new_with_text(text).ok()) {
+        Some((speech, utterance)) => {
+            // Only one of the two fires, so the closure is called exactly once
+            let done = Closure::once_into_js(play_next);
+            utterance.set_onend(Some(done.unchecked_ref()));
+            utterance.set_onerror(Some(done.unchecked_ref()));
+            speech.speak(&utterance);
         }
-
-        // Fall back to TTS if we couldn't create the audio element
-        tts_play(text)
-    })
+        None => play_next(),
+    }
 }
 
 #[component]
 pub fn TimerPage() -> impl IntoView {
-    // Get exercise ID from the URL
+    // Get routine ID from the URL
     let params = use_params_map();
-    let exercise_id = params.with(|p| p.get("id").unwrap_or_default());
+    let routine_id = params.with(|p| p.get("id").unwrap_or_default());
 
-    // Routine data from our data module
-    let routines = get_routines();
+    // Custom routines only show up once settings are loaded, so look the routine up reactively
+    let SettingsContext { settings, loaded, .. } = expect_context::<SettingsContext>();
+    let routine = Memo::new(move |_| settings.with(|s| find_routine(&routine_id, s)));
 
-    // Find the selected routine using StoredValue
-    let Some(r) = routines.iter().find(|r| r.id == exercise_id).cloned() else {
-        return view! {
+    move || match routine.get() {
+        Some(routine) => view! { <Timer routine /> }.into_any(),
+        // Until then an unknown id may still be a custom routine
+        None if !loaded.get() => view! {
+          <div class="p-4 mx-auto max-w-lg">
+            <div class="p-6 bg-white rounded-lg shadow-md">
+              <p class="text-center text-gray-500 animate-pulse">Loading workout...</p>
+            </div>
+          </div>
+        }
+        .into_any(),
+        None => view! {
           <div class="p-4 mx-auto max-w-lg">
             <div class="p-6 bg-white rounded-lg shadow-md">
               <h2 class="mb-4 text-xl font-bold text-center">Exercise Not Found</h2>
@@ -99,126 +137,292 @@ pub fn TimerPage() -> impl IntoView {
             </div>
           </div>
         }
-        .into_any();
-    };
-    let routine = StoredValue::new(r);
+        .into_any(),
+    }
+}
+
+#[component]
+fn Timer(routine: Routine) -> impl IntoView {
+    let resume_requested = use_query_map().with_untracked(|q| q.get("resume").is_some());
+    let routine = StoredValue::new(routine);
 
     // Get settings context
     let SettingsContext {
         settings,
         update_settings,
+        loaded,
     } = expect_context::<SettingsContext>();
 
-    // Initialize interval (1 second = 1000ms)
+    // The interval only drives re-rendering; elapsed time comes from the wall clock
+    // so throttled timers (background tabs, locked phones) can't stretch the workout.
     let interval = 25;
     let UseIntervalReturn {
         counter,
-        pause,
-        resume,
+        pause: pause_ticks,
+        resume: resume_ticks,
         is_active,
-        reset,
         ..
     } = use_interval_with_options(interval, UseIntervalOptions::default().immediate(false));
+    let clock = RwSignal::new(WorkoutClock::new());
+
+    // Point in the workout up to which cues have been spoken
+    let cues_spoken_until = StoredValue::new(Duration::ZERO);
+
+    // Settings snapshot of a resumed session, and a saved session we could resume
+    let session_settings = RwSignal::new(None::<WorkoutSettings>);
+    let saved_session = RwSignal::new(None::<WorkoutSession>);
+
+    // When this run of the workout started, and whether it has been logged yet
+    let started_at = StoredValue::new(None::<DateTime<Utc>>);
+    let recorded = StoredValue::new(false);
+
+    // Rounds tapped during AMRAP stages of this run
+    let rounds = RwSignal::new(Vec::<StageRounds>::new());
+
+    // Append this run to the workout history, using the settings it was run with
+    let record_workout = move |elapsed: Duration| {
+        let now = Utc::now();
+        let mut new_settings = settings.get_untracked();
+        let record = {
+            let timing = session_settings.get_untracked();
+            let timing = timing.as_ref().unwrap_or(&new_settings);
+            let started_at = started_at.get_value().unwrap_or(now);
+            WorkoutRecord {
+                rounds: rounds.get_untracked(),
+                ..WorkoutRecord::new(&routine.read_value(), timing, started_at, now, elapsed)
+            }
+        };
+        new_settings.history.push(record);
+        update_settings.run(new_settings);
+        recorded.set_value(true);
+    };
 
-    // Store already spoken announcements to avoid duplicates
-    let spoken_announcements = StoredValue::new(HashSet::<(usize, String)>::new());
+    // Log a saved session that will never be resumed: one that expired, or one
+    // about to be overwritten by a new run
+    let log_session = move |session: WorkoutSession| {
+        let mut new_settings = settings.get_untracked();
+        new_settings.history.push(session.record(&new_settings));
+        update_settings.run(new_settings);
+    };
 
-    let time_left = move || {
-        let time_spent = Duration::from_secs_f64(counter.get() as f64 * interval as f64 / 1000.0);
-        routine
-            .read_value()
-            .duration()
-            .checked_sub(time_spent)
-            .unwrap_or_default()
+    let pause = move || {
+        pause_ticks();
+        clock.update(|c| c.pause(Instant::now()));
+    };
+    let resume = move || {
+        saved_session.set(None);
+        if started_at.get_value().is_none() {
+            let window_mins = settings.with_untracked(|s| s.resume_window_mins);
+            if let Some(saved) = WorkoutSession::load(Utc::now(), window_mins) {
+                log_session(saved.into_session());
+                WorkoutSession::clear();
+            }
+            started_at.set_value(Some(Utc::now()));
+        }
+        clock.update(|c| c.start(Instant::now()));
+        resume_ticks();
+    };
+    let reset = {
+        let pause = pause.clone();
+        move || {
+            pause();
+            // Log a workout that was abandoned part way through
+            let elapsed = clock.with_untracked(|c| c.elapsed(Instant::now()));
+            if !elapsed.is_zero() && !recorded.get_value() {
+                record_workout(elapsed);
+            }
+            clock.update(|c| c.reset());
+            cues_spoken_until.set_value(Duration::ZERO);
+            clear_audio_queue();
+            session_settings.set(None);
+            started_at.set_value(None);
+            recorded.set_value(false);
+            rounds.set(Vec::new());
+            WorkoutSession::clear();
+        }
     };
 
-    // Format time as MM:SS
-    let format_time = move |seconds: u32| {
-        let minutes = seconds / 60;
-        let remaining_seconds = seconds % 60;
-        format!("{:02}:{:02}", minutes, remaining_seconds)
+    // Stage schedule for this routine under the current settings, or under the
+    // settings a resumed session was started with
+    let schedule = Memo::new(move |_| match session_settings.get() {
+        Some(s) => Schedule::new(&routine.read_value(), &s),
+        None => settings.with(|s| Schedule::new(&routine.read_value(), s)),
+    });
+
+    let elapsed = move || {
+        counter.track();
+        clock.with(|c| c.elapsed(Instant::now()))
     };
 
-    // Helper function to speak text using speech synthesis
-    let speak = move |index: usize, text: &str| {
-        // Create a key from index and text
-        let announcement_key = (index, text.to_string());
+    let time_left = move || schedule.read().duration().checked_sub(elapsed()).unwrap_or_default();
+
+    // The current AMRAP stage, if any. Only changes at stage boundaries so the
+    // tap target isn't re-rendered under the user's finger.
+    let amrap_stage = Memo::new(move |_| {
+        let elapsed = elapsed().as_secs_f64();
+        schedule
+            .with(|s| s.stage_at_t(elapsed))
+            .and_then(|(index, stage, ..)| stage.amrap.then_some((index, stage.label)))
+    });
 
-        // Check if this announcement has already been spoken
-        let mut spoken = spoken_announcements.get_value();
-        if !spoken.contains(&announcement_key) {
-            // Add it to the set of spoken announcements
-            spoken.insert(announcement_key);
-            spoken_announcements.set_value(spoken);
+    // Jump to a stage boundary. Cues are picked up from the target, so the stage
+    // we land on is announced and the ones skipped over are not.
+    let seek = move |target: Duration| {
+        clear_audio_queue();
+        cues_spoken_until.set_value(target);
+        clock.update(|c| c.seek(target, Instant::now()));
+    };
+    let previous_stage = move || seek(schedule.with(|s| s.previous_stage_start(elapsed())));
+    let next_stage = move || {
+        if let Some(target) = schedule.with(|s| s.next_stage_start(elapsed())) {
+            seek(target);
+        }
+    };
+    let has_next_stage = move || schedule.with(|s| s.next_stage_start(elapsed()).is_some());
+
+    let resume_session = {
+        let resume = resume.clone();
+        move |session: WorkoutSession| {
+            session_settings.set(Some(session.settings.clone()));
+            started_at.set_value(Some(session.started_at()));
+            rounds.set(session.rounds.clone());
+            seek(session.elapsed());
+            saved_session.set(None);
+            if !session.paused {
+                resume();
+            }
+        }
+    };
 
-            // Get the voice from settings
-            let voice = &settings.get_untracked().voice;
+    // Look for an interrupted session of this routine on the client side
+    Effect::new({
+        let resume_session = resume_session.clone();
+        move |_| {
+            // Wait for the stored settings, which an expired session is logged to
+            if !loaded.get() {
+                return;
+            }
+            let window_mins = settings.with_untracked(|s| s.resume_window_mins);
+            let session = match WorkoutSession::load(Utc::now(), window_mins) {
+                Some(SavedSession::Resumable(session)) => Some(session),
+                Some(SavedSession::Expired(session)) => {
+                    log_session(session);
+                    None
+                }
+                None => None,
+            }
+            .filter(|session| session.routine_id == routine.read_value().id);
+            match session {
+                Some(session) if resume_requested => resume_session(session),
+                session => saved_session.set(session),
+            }
+        }
+    });
 
-            // Speak the announcement with the selected voice
-            play_audio(text, voice);
+    // Persist progress so the workout can be resumed after a reload or navigation
+    let save_session = move || {
+        let (elapsed, running) = clock.with_untracked(|c| (c.elapsed(Instant::now()), c.is_running()));
+        if elapsed.is_zero() || elapsed >= schedule.with_untracked(|s| s.duration()) {
+            return;
         }
+        let snapshot = session_settings
+            .get_untracked()
+            .unwrap_or_else(|| settings.get_untracked());
+        WorkoutSession::new(
+            routine.read_value().id.clone(),
+            &snapshot,
+            elapsed,
+            !running,
+            started_at.get_value().unwrap_or_else(Utc::now),
+            Utc::now(),
+            rounds.get_untracked(),
+        )
+        .save();
+    };
+    let elapsed_secs = Memo::new(move |_| elapsed().as_secs());
+    Effect::new(move |_| {
+        elapsed_secs.track();
+        is_active.track();
+        rounds.track();
+        save_session();
+    });
+    on_cleanup(save_session);
+
+    // Format time as MM:SS
+    let format_time = move |seconds: u32| {
+        let minutes = seconds / 60;
+        let remaining_seconds = seconds % 60;
+        format!("{:02}:{:02}", minutes, remaining_seconds)
     };
 
-    // Effect to handle stage announcements and countdowns
+    // Everything to announce during the workout
+    let cue_plan = Memo::new(move |_| {
+        let options = settings.with(CueOptions::from_settings);
+        schedule.with(|s| CuePlan::new(s, options))
+    });
+
+    // Queue the cues due since the last tick. A throttled tick may cover several,
+    // which are spoken one after another so none is skipped.
     Effect::new(move |_| {
         // Only process when the timer is active
         if !is_active.get() {
             return;
         }
 
-        let routine_val = routine.get_value();
-        let elapsed = routine_val.duration().as_secs_f64() - time_left().as_secs_f64();
-
-        if let Some((stage_index, current, _next, time_in_stage)) = routine_val.stage_at_t(elapsed) {
-            // Calculate remaining time in this stage
-            let remaining = current.duration.as_secs_f64() - time_in_stage;
-
-            // Announce new stage (only once when it changes)
-            if time_in_stage < 1_f64 {
-                // Announce the new stage name
-                let announcement = current.label.clone();
-                speak(stage_index, &announcement);
-            }
-            // Handle countdown when approaching the end of a stage
-            if remaining <= 2.5 {
-                speak(stage_index, "three two one");
-            }
-        } else {
-            speak(0, "Workout Complete");
+        let now = elapsed();
+        let from = cues_spoken_until.get_value();
+        if now <= from {
+            return;
         }
-    });
+        cues_spoken_until.set_value(now);
 
-    // Reset spoken announcements when timer is reset
-    Effect::new(move |_| {
-        if counter.get() == 0 {
-            spoken_announcements.set_value(HashSet::new());
-        }
+        let voice = settings.with_untracked(|s| s.voice.clone());
+        cue_plan.with(|plan| {
+            for cue in plan.between(from, now) {
+                play_audio(&cue.text, &voice);
+            }
+        });
     });
 
     // Effect to handle routine completion
     Effect::new(move |_| {
         if time_left().as_secs() == 0 && is_active.get() {
-            // Record completion
-            let mut new_settings = settings.get();
-            let routine_name = routine.get_value().name.clone();
-            let now = Utc::now();
-
-            // Only update if there's no previous completion or it was more than 10 seconds ago
-            let should_update = match new_settings.routine_completions.get(&routine_name) {
-                Some(last_completion) => (now - *last_completion).num_seconds() > 10,
-                None => true,
-            };
-
-            if should_update {
-                new_settings.routine_completions.insert(routine_name, now);
-                update_settings.run(new_settings);
+            WorkoutSession::clear();
+
+            // Record completion once per run
+            if !recorded.get_value() {
+                record_workout(schedule.with_untracked(|s| s.duration()));
             }
         }
     });
 
+    // Big 3-2-1 over the timer as the stage before the first exercise ends
+    let countdown = move || {
+        if !settings.with(|s| s.countdown_overlay) || !is_active.get() {
+            return None;
+        }
+        let schedule = schedule.get();
+        let elapsed = elapsed().as_secs_f64();
+        let (index, current, _, time_in_stage) = schedule.stage_at_t(elapsed)?;
+        if schedule.first_exercise_index() != Some(index + 1) {
+            return None;
+        }
+        countdown_digit(current.duration.saturating_sub(Duration::from_secs_f64(time_in_stage)))
+    };
+
     // View
     view! {
       <div class="p-4 mx-auto max-w-lg">
+        {move || {
+          countdown()
+            .map(|digit| {
+              view! {
+                <div class="flex fixed inset-0 z-50 justify-center items-center bg-black bg-opacity-60 pointer-events-none">
+                  <div class="font-bold text-white text-9xl">{digit}</div>
+                </div>
+              }
+            })
+        }}
         {move || {
           let r = routine.get_value();
           view! {
@@ -246,33 +450,58 @@ pub fn TimerPage() -> impl IntoView {
                   <div
                     class="h-full bg-gradient-to-r from-blue-500 to-indigo-600"
                     style:width=move || {
-                      let duration = routine.get_value().duration().as_secs();
+                      let duration = schedule.read().duration().as_secs();
                       format!("{}%", (time_left().as_secs() as f32 / duration as f32) * 100.0)
                     }
                   ></div>
                 </div>
                 {move || {
-                  let routine = routine.get_value();
-                  let elapsed = routine.duration().as_secs_f64() - time_left().as_secs_f64();
-                  if let Some((_stage_index, current, next, time_in_stage)) = routine
+                  let schedule = schedule.get();
+                  let elapsed = schedule.duration().as_secs_f64() - time_left().as_secs_f64();
+                  if let Some((_stage_index, current, next, time_in_stage)) = schedule
                     .stage_at_t(elapsed)
                   {
                     let stage_progress_pct = (time_in_stage / (current.duration.as_secs_f64()))
                       * 100.0;
                     view! {
                       <div class="mt-4 text-center">
+                        {current
+                          .phase
+                          .map(|phase| {
+                            view! {
+                              <div class="mb-1 text-sm font-semibold tracking-wide text-sky-600 uppercase">
+                                {phase.label()}
+                              </div>
+                            }
+                          })}
                         <div class="text-5xl font-semibold">{current.label.clone()}</div>
+                        {current
+                          .side
+                          .map(|side| {
+                            view! { <div class="mt-1 text-lg text-gray-600">{side.label()}</div> }
+                          })}
+                        {current
+                          .reps
+                          .map(|reps| {
+                            view! {
+                              <div class="mt-1 mb-2 text-lg text-gray-600">
+                                {format!("{} reps, then rest until the minute is up", reps)}
+                              </div>
+                            }
+                          })}
                         <div class="overflow-hidden mb-2 h-2 bg-gray-200 rounded-full">
                           <div
                             class=move || {
-                              if current.is_high_intensity {
+                              if current.phase.is_some() {
+                                "h-full bg-gradient-to-r from-sky-400 to-violet-400"
+                              } else if current.is_high_intensity {
                                 "h-full bg-gradient-to-r from-red-500 to-orange-400"
                               } else {
                                 "h-full bg-gradient-to-r from-green-400 to-teal-500"
                               }
                             }
                             style:width=move || {
-                              if current.is_high_intensity {
+                              if current.is_high_intensity || current.phase.is_some() {
                                 format!("{}%", stage_progress_pct)
                               } else {
                                 format!("{}%", 100.0 - stage_progress_pct)
@@ -300,8 +529,83 @@ pub fn TimerPage() -> impl IntoView {
                       .into_any()
                   }
                 }}
-              </div> <div class="text-center">
+                {move || {
+                  amrap_stage
+                    .get()
+                    .map(|(index, label)| {
+                      let count = move || {
+                        rounds
+                          .read()
+                          .iter()
+                          .find(|r| r.stage == index)
+                          .map_or(0, |r| r.rounds)
+                      };
+                      view! {
+                        <button
+                          class="py-8 mt-4 w-full text-3xl font-bold text-white bg-orange-500 rounded-lg transition-colors select-none hover:bg-orange-600 active:bg-orange-700"
+                          on:click=move |_| rounds.update(|rounds| tap_round(rounds, index, &label))
+                        >
+                          <div>{count}</div>
+                          <div class="text-sm font-normal uppercase">"Tap for each round"</div>
+                        </button>
+                      }
+                    })
+                }}
+                <Show when=move || time_left().is_zero() && !rounds.read().is_empty()>
+                  <div class="p-4 mt-4 bg-orange-50 rounded-lg">
+                    <h3 class="mb-2 font-semibold text-gray-800">Rounds</h3>
+                    <ul class="space-y-1 text-sm text-gray-700">
+                      {move || {
+                        rounds
+                          .get()
+                          .into_iter()
+                          .map(|r| {
+                            view! {
+                              <li class="flex justify-between">
+                                <span>{r.label}</span>
+                                <span class="font-semibold">{r.rounds}</span>
+                              </li>
+                            }
+                          })
+                          .collect::<Vec<_>>()
+                      }}
+                    </ul>
+                    <div class="flex justify-between pt-2 mt-2 font-semibold text-gray-800 border-t border-orange-200">
+                      <span>Total</span>
+                      <span>{move || rounds.read().iter().map(|r| r.rounds).sum::<u32>()}</span>
+                    </div>
+                  </div>
+                </Show>
+              </div>
+              {
+                let resume_session = resume_session.clone();
+                move || {
+                  let resume_session = resume_session.clone();
+                  saved_session
+                    .get()
+                    .map(|session| {
+                      let elapsed = format_time(session.elapsed().as_secs() as u32);
+                      view! {
+                        <div class="mb-4 text-center">
+                          <button
+                            class="py-2 px-4 text-white bg-indigo-500 rounded transition-colors hover:bg-indigo-600"
+                            on:click=move |_| resume_session(session.clone())
+                          >
+                            "Resume workout at "
+                            {elapsed}
+                          </button>
+                        </div>
+                      }
+                    })
+                }
+              } <div class="text-center">
                 <div class="flex justify-center space-x-4">
+                  <button
+                    class="py-2 px-4 text-white bg-blue-500 rounded transition-colors hover:bg-blue-600"
+                    on:click=move |_| previous_stage()
+                  >
+                    "Previous"
+                  </button>
                   {if is_active.get() {
                     view! {
                       <button
@@ -338,13 +642,20 @@ pub fn TimerPage() -> impl IntoView {
                   >
                     "Reset"
                   </button>
+                  <button
+                    class="py-2 px-4 text-white bg-blue-500 rounded transition-colors hover:bg-blue-600 disabled:opacity-50"
+                    disabled=move || !has_next_stage()
+                    on:click=move |_| next_stage()
+                  >
+                    "Next"
+                  </button>
                   <button
                     class="hidden py-2 px-4 text-white bg-purple-500 rounded transition-colors hover:bg-purple-600"
                     on:click=move |_| {
                       let mut new_settings = settings.get();
                       new_settings
-                        .routine_completions
-                        .insert(routine.get_value().name.clone(), Utc::now());
+                        .history
+                        .push(WorkoutRecord::completed(&routine.read_value(), &new_settings, Utc::now()));
                       update_settings.run(new_settings);
                     }
                   >
@@ -358,5 +669,4 @@ pub fn TimerPage() -> impl IntoView {
         }}
       </div>
     }
-    .into_any()
 }
@@ -0,0 +1,369 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+use crate::components::settings::WorkoutSettings;
+
+/// Version of the export format written by this build.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Settings written by every export since version 1. Without them a document is
+/// not a backup, and merging it would replace the user's preferences with defaults.
+const REQUIRED_SETTINGS: [&str; 10] = [
+    "high_intensity_duration_secs",
+    "rest_exercise_duration_secs",
+    "rest_set_duration_secs",
+    "sets",
+    "history",
+    "voice",
+    "resume_window_mins",
+    "weekly_goal",
+    "custom_routines",
+    "routine_overrides",
+];
+
+/// Everything stored locally, as written by the export button.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Settings including custom routines, overrides and workout history.
+    pub settings: WorkoutSettings,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// Not JSON, or not shaped like a backup.
+    Invalid(String),
+    MissingVersion,
+    /// Written by a newer version of the app.
+    UnsupportedVersion(u64),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Invalid(err) => write!(f, "Not a valid backup: {}", err),
+            ImportError::MissingVersion => write!(f, "Not a valid backup: missing \"version\""),
+            ImportError::UnsupportedVersion(version) => write!(
+                f,
+                "Backup version {} is newer than this app supports (version {})",
+                version, BACKUP_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// What an import changed. Imported values win; every local value they
+/// replaced is listed as a conflict.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub routines_added: usize,
+    pub history_added: usize,
+    pub conflicts: Vec<String>,
+}
+
+impl Backup {
+    pub fn new(settings: &WorkoutSettings, exported_at: DateTime<Utc>) -> Self {
+        Backup {
+            version: BACKUP_VERSION,
+            exported_at,
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Parse an exported document, checking the version before the contents.
+    pub fn parse(json: &str) -> Result<Self, ImportError> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|err| ImportError::Invalid(err.to_string()))?;
+        match value.get("version").map(|version| version.as_u64()) {
+            None => return Err(ImportError::MissingVersion),
+            Some(None) => return Err(ImportError::Invalid("\"version\" is not a number".to_string())),
+            Some(Some(version)) if version > BACKUP_VERSION as u64 => {
+                return Err(ImportError::UnsupportedVersion(version))
+            }
+            Some(Some(_)) => {}
+        }
+        let backup = serde_json::from_str(json).map_err(|err| ImportError::Invalid(err.to_string()))?;
+        // Deserializing fills gaps with defaults, so check the fields were actually there
+        let settings = value.get("settings").and_then(|settings| settings.as_object());
+        if let Some(field) = REQUIRED_SETTINGS
+            .iter()
+            .find(|field| !settings.is_some_and(|settings| settings.contains_key(**field)))
+        {
+            return Err(ImportError::Invalid(format!(
+                "missing field `{}` in \"settings\"",
+                field
+            )));
+        }
+        Ok(backup)
+    }
+}
+
+/// Merge imported settings into the current ones.
+///
+/// Presets are matched by name, custom routines and overrides by routine id, and history
+/// records are added unless an identical record already exists.
+pub fn merge(current: &WorkoutSettings, imported: WorkoutSettings) -> (WorkoutSettings, MergeReport) {
+    let mut report = MergeReport::default();
+    let mut merged = current.clone();
+
+    let mut preference = |name: &str, changed: bool| {
+        if changed {
+            report.conflicts.push(format!("{} replaced by imported value", name));
+        }
+    };
+    preference(
+        "High intensity duration",
+        current.high_intensity_duration_secs != imported.high_intensity_duration_secs,
+    );
+    preference(
+        "Exercise rest duration",
+        current.rest_exercise_duration_secs != imported.rest_exercise_duration_secs,
+    );
+    preference(
+        "Set rest duration",
+        current.rest_set_duration_secs != imported.rest_set_duration_secs,
+    );
+    preference("Sets", current.sets != imported.sets);
+    preference(
+        "Prepare duration",
+        current.prepare_duration_secs != imported.prepare_duration_secs,
+    );
+    preference(
+        "Countdown overlay",
+        current.countdown_overlay != imported.countdown_overlay,
+    );
+    preference("Halfway cue", current.halfway_cue != imported.halfway_cue);
+    preference("Ten seconds cue", current.ten_seconds_cue != imported.ten_seconds_cue);
+    preference("Voice", current.voice != imported.voice);
+    preference(
+        "Resume window",
+        current.resume_window_mins != imported.resume_window_mins,
+    );
+    preference("Weekly goal", current.weekly_goal != imported.weekly_goal);
+    preference("Warm-up", current.warm_up_secs != imported.warm_up_secs);
+    preference("Cool-down", current.cool_down_secs != imported.cool_down_secs);
+    merged.high_intensity_duration_secs = imported.high_intensity_duration_secs;
+    merged.rest_exercise_duration_secs = imported.rest_exercise_duration_secs;
+    merged.rest_set_duration_secs = imported.rest_set_duration_secs;
+    merged.sets = imported.sets;
+    merged.prepare_duration_secs = imported.prepare_duration_secs;
+    merged.countdown_overlay = imported.countdown_overlay;
+    merged.halfway_cue = imported.halfway_cue;
+    merged.ten_seconds_cue = imported.ten_seconds_cue;
+    merged.voice = imported.voice;
+    merged.resume_window_mins = imported.resume_window_mins;
+    merged.weekly_goal = imported.weekly_goal;
+    merged.warm_up_secs = imported.warm_up_secs;
+    merged.cool_down_secs = imported.cool_down_secs;
+
+    for preset in imported.presets {
+        match merged.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) if *existing == preset => {}
+            Some(existing) => {
+                report
+                    .conflicts
+                    .push(format!("Preset \"{}\" replaced by imported version", existing.name));
+                *existing = preset;
+            }
+            None => merged.presets.push(preset),
+        }
+    }
+
+    for routine in imported.custom_routines {
+        match merged.custom_routines.iter_mut().find(|r| r.id == routine.id) {
+            Some(existing) if *existing == routine => {}
+            Some(existing) => {
+                report
+                    .conflicts
+                    .push(format!("Routine \"{}\" replaced by imported version", existing.name));
+                *existing = routine;
+            }
+            None => {
+                report.routines_added += 1;
+                merged.custom_routines.push(routine);
+            }
+        }
+    }
+
+    for (id, overrides) in imported.routine_overrides {
+        match merged.routine_overrides.insert(id.clone(), overrides.clone()) {
+            Some(existing) if existing != overrides => {
+                report
+                    .conflicts
+                    .push(format!("Overrides for routine {} replaced by imported ones", id));
+            }
+            _ => {}
+        }
+    }
+
+    for record in imported.history {
+        if !merged.history.contains(&record) {
+            report.history_added += 1;
+            merged.history.push(record);
+        }
+    }
+    merged.history.sort_by_key(|record| record.started_at);
+
+    (merged, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::routine_card::{Routine, TimingOverrides};
+    use crate::components::settings::Preset;
+    use crate::data::routines::get_routines;
+    use crate::workout::history::WorkoutRecord;
+    use chrono::TimeZone;
+
+    fn custom_routine(id: &str, name: &str) -> Routine {
+        Routine {
+            id: id.to_string(),
+            name: name.to_string(),
+            exercises: vec!["Squats".into(), "Lunges".into()],
+            ..Default::default()
+        }
+    }
+
+    fn record(day: u32) -> WorkoutRecord {
+        let ended_at = Utc.with_ymd_and_hms(2023, 5, day, 10, 0, 0).unwrap();
+        WorkoutRecord::completed(&get_routines()[0], &WorkoutSettings::default(), ended_at)
+    }
+
+    #[test]
+    fn test_backup_roundtrip() {
+        let settings = WorkoutSettings {
+            sets: 5,
+            custom_routines: vec![custom_routine("custom-1", "Legs")],
+            history: vec![record(1)],
+            ..WorkoutSettings::default()
+        };
+        let backup = Backup::new(&settings, Utc.with_ymd_and_hms(2023, 5, 15, 10, 0, 0).unwrap());
+        assert_eq!(Backup::parse(&backup.to_json()), Ok(backup));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(Backup::parse("not json"), Err(ImportError::Invalid(_))));
+        assert_eq!(Backup::parse(r#"{"settings": {}}"#), Err(ImportError::MissingVersion));
+        assert_eq!(
+            Backup::parse(r#"{"version": 99, "settings": {}}"#),
+            Err(ImportError::UnsupportedVersion(99))
+        );
+        assert!(matches!(
+            Backup::parse(r#"{"version": "1", "settings": {}}"#),
+            Err(ImportError::Invalid(_))
+        ));
+
+        // Schema errors inside the settings are reported, not replaced by defaults
+        let result =
+            Backup::parse(r#"{"version": 1, "exported_at": "2023-05-15T10:00:00Z", "settings": {"sets": "three"}}"#);
+        match result {
+            Err(ImportError::Invalid(err)) => assert!(err.contains("invalid type"), "{}", err),
+            other => panic!("expected a schema error, got {:?}", other),
+        }
+
+        // An empty settings object would reset every preference on merge
+        assert_eq!(
+            Backup::parse(r#"{"version": 1, "exported_at": "2023-05-15T10:00:00Z", "settings": {}}"#),
+            Err(ImportError::Invalid(
+                "missing field `high_intensity_duration_secs` in \"settings\"".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let current = WorkoutSettings {
+            sets: 3,
+            custom_routines: vec![custom_routine("custom-1", "Legs"), custom_routine("custom-2", "Arms")],
+            routine_overrides: [(
+                "4".to_string(),
+                TimingOverrides {
+                    sets: Some(2),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            history: vec![record(1), record(3)],
+            ..WorkoutSettings::default()
+        };
+        let mut renamed = custom_routine("custom-2", "Arms");
+        renamed.exercises.push("Burpees".into());
+        let imported = WorkoutSettings {
+            sets: 4,
+            custom_routines: vec![
+                custom_routine("custom-1", "Legs"),
+                renamed.clone(),
+                custom_routine("custom-3", "Core"),
+            ],
+            routine_overrides: [(
+                "4".to_string(),
+                TimingOverrides {
+                    sets: Some(6),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            history: vec![record(2), record(3)],
+            ..WorkoutSettings::default()
+        };
+
+        let (merged, report) = merge(&current, imported);
+
+        assert_eq!(merged.sets, 4);
+        assert_eq!(merged.custom_routines.len(), 3);
+        assert_eq!(merged.custom_routines[1], renamed);
+        assert_eq!(merged.routine_overrides["4"].sets, Some(6));
+        assert_eq!(merged.history, vec![record(1), record(2), record(3)]);
+        assert_eq!(
+            report,
+            MergeReport {
+                routines_added: 1,
+                history_added: 1,
+                conflicts: vec![
+                    "Sets replaced by imported value".to_string(),
+                    "Routine \"Arms\" replaced by imported version".to_string(),
+                    "Overrides for routine 4 replaced by imported ones".to_string(),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_presets_by_name() {
+        let mut imported = WorkoutSettings::default();
+        imported.presets[0].sets = 5;
+        imported.presets.push(Preset {
+            name: "Sprint".to_string(),
+            ..imported.presets[2].clone()
+        });
+
+        let (merged, report) = merge(&WorkoutSettings::default(), imported.clone());
+
+        assert_eq!(merged.presets, imported.presets);
+        assert_eq!(
+            report.conflicts,
+            vec!["Preset \"Low\" replaced by imported version".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_merge_into_same_settings_changes_nothing() {
+        let settings = WorkoutSettings {
+            custom_routines: vec![custom_routine("custom-1", "Legs")],
+            history: vec![record(1)],
+            ..WorkoutSettings::default()
+        };
+        let (merged, report) = merge(&settings, settings.clone());
+        assert_eq!(merged, settings);
+        assert_eq!(report, MergeReport::default());
+    }
+}
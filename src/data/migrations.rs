@@ -0,0 +1,194 @@
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::components::settings::WorkoutSettings;
+use crate::workout::history::migrate_completions;
+
+/// Schema version written with `WorkoutSettings`.
+///
+/// 1. Unversioned settings with a `routine_completions` map of the last
+///    completion time per routine name.
+/// 2. Completions are kept as a full `history` of workout records.
+///
+/// Fields added since then have defaults, so older settings still load without
+/// a migration; `fixtures/settings_v2_current.json` pins the current shape.
+pub const SETTINGS_VERSION: u64 = 2;
+
+/// Upgrades settings JSON in place from one version to the next.
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[i]` upgrades from version `i + 1` to `i + 2`.
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize - 1] = [completions_to_history];
+
+/// Upgrade stored settings JSON to the current schema version.
+///
+/// Settings without a version predate versioning and are treated as version 1.
+pub fn migrate_settings(value: Value) -> Result<Value, String> {
+    let Value::Object(mut map) = value else {
+        return Err("settings must be a JSON object".to_string());
+    };
+    let version = match map.get("version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .filter(|&version| version >= 1)
+            .ok_or_else(|| format!("invalid settings version {}", version))?,
+    };
+    if version > SETTINGS_VERSION {
+        return Err(format!(
+            "settings version {} is newer than this app supports (version {})",
+            version, SETTINGS_VERSION
+        ));
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        migration(&mut map).map_err(|err| format!("migrating settings from version {}: {}", from + 1, err))?;
+        map.insert("version".to_string(), Value::from(from as u64 + 2));
+    }
+    Ok(Value::Object(map))
+}
+
+fn completions_to_history(map: &mut Map<String, Value>) -> Result<(), String> {
+    let Some(completions) = map.remove("routine_completions") else {
+        return Ok(());
+    };
+    let completions: HashMap<String, DateTime<Utc>> =
+        serde_json::from_value(completions).map_err(|err| format!("routine_completions: {}", err))?;
+
+    // Routine lookup and timing come from the rest of the settings, which are
+    // otherwise unchanged in version 2
+    let mut current = map.clone();
+    current.insert("version".to_string(), Value::from(2));
+    let settings: WorkoutSettings = serde_json::from_value(Value::Object(current)).map_err(|err| err.to_string())?;
+
+    let mut history = settings.history.clone();
+    history.extend(migrate_completions(&completions, &settings));
+    history.sort_by_key(|record| record.started_at);
+    map.insert(
+        "history".to_string(),
+        serde_json::to_value(history).map_err(|err| err.to_string())?,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workout::history::last_completion;
+    use crate::workout::schedule::WorkoutMode;
+    use chrono::TimeZone;
+
+    const FIXTURES: [&str; SETTINGS_VERSION as usize] = [
+        include_str!("fixtures/settings_v1.json"),
+        include_str!("fixtures/settings_v2.json"),
+    ];
+
+    /// Every field the current build writes, so a field can't be renamed or dropped unnoticed.
+    const CURRENT_FIXTURE: &str = include_str!("fixtures/settings_v2_current.json");
+
+    fn load(json: &str) -> WorkoutSettings {
+        serde_json::from_str(json).expect("Failed to deserialize")
+    }
+
+    #[test]
+    fn test_every_fixture_migrates_to_current_version() {
+        for (index, fixture) in FIXTURES.iter().enumerate() {
+            let value = migrate_settings(serde_json::from_str(fixture).unwrap())
+                .unwrap_or_else(|err| panic!("version {}: {}", index + 1, err));
+            assert_eq!(value["version"], SETTINGS_VERSION, "version {}", index + 1);
+
+            // Saving and loading again doesn't change anything
+            let settings = load(fixture);
+            assert_eq!(load(&serde_json::to_string(&settings).unwrap()), settings);
+        }
+    }
+
+    #[test]
+    fn test_migrate_v1() {
+        let settings = load(FIXTURES[0]);
+
+        assert_eq!(settings.sets, 4);
+        assert_eq!(settings.voice, "vlad");
+        assert_eq!(settings.custom_routines[0].exercises[1].duration_secs, Some(60));
+        assert_eq!(settings.routine_overrides["3"].high_intensity_duration_secs, Some(30));
+
+        // Completions become finished records, matched to routines by name
+        assert_eq!(settings.history.len(), 2);
+        assert!(settings.history.iter().all(|record| record.finished));
+        assert_eq!(
+            last_completion(&settings.history, "3"),
+            Some(Utc.with_ymd_and_hms(2024, 3, 2, 8, 15, 0).unwrap())
+        );
+        assert_eq!(
+            last_completion(&settings.history, "custom-1709280000000"),
+            Some(Utc.with_ymd_and_hms(2024, 3, 4, 18, 30, 0).unwrap())
+        );
+        // Timing includes the routine's overrides at the time of migration
+        assert_eq!(settings.history[0].timing.high_intensity_duration_secs, 30);
+        assert_eq!(settings.history[1].timing.sets, 2);
+    }
+
+    #[test]
+    fn test_migrate_v2() {
+        let settings = load(FIXTURES[1]);
+
+        assert_eq!(settings.weekly_goal, 4);
+        assert_eq!(settings.history.len(), 1);
+        assert_eq!(settings.history[0].work_secs, 480);
+
+        // Fields added since version 2 was introduced fall back to their defaults
+        let defaults = WorkoutSettings::default();
+        assert_eq!(settings.prepare_duration_secs, defaults.prepare_duration_secs);
+        assert!(!settings.countdown_overlay && !settings.halfway_cue && !settings.ten_seconds_cue);
+        assert_eq!((settings.warm_up_secs, settings.cool_down_secs), (0, 0));
+        assert_eq!(settings.presets, defaults.presets);
+        let timing = &settings.history[0].timing;
+        assert_eq!(timing.mode, WorkoutMode::Standard);
+        assert!(timing.work_ladder.is_empty());
+        assert_eq!((timing.warm_up_secs, timing.cool_down_secs), (0, 0));
+        assert!(settings.history[0].rounds.is_empty());
+    }
+
+    #[test]
+    fn test_current_shape() {
+        let settings = load(CURRENT_FIXTURE);
+        assert_eq!(settings.prepare_duration_secs, 5);
+        assert!(settings.countdown_overlay && settings.halfway_cue && settings.ten_seconds_cue);
+        assert_eq!((settings.warm_up_secs, settings.cool_down_secs), (60, 90));
+        assert_eq!(settings.presets[0].name, "Long Intervals");
+        assert_eq!(settings.history[0].rounds[0].rounds, 4);
+        assert_eq!(settings.history[0].timing.work_ladder, [20, 40, 20]);
+
+        let routine = &settings.custom_routines[0];
+        assert_eq!(routine.overrides.work_ladder, [20, 40, 20]);
+        assert!(routine.exercises[1].amrap);
+        assert_eq!(routine.exercises[2].reps, Some(12));
+        assert_eq!(settings.routine_overrides["3"].mode, Some(WorkoutMode::Tabata));
+        assert_eq!(settings.routine_overrides["5"].mode, Some(WorkoutMode::Emom));
+
+        // Saving writes back exactly the same document
+        let fixture: Value = serde_json::from_str(CURRENT_FIXTURE).unwrap();
+        assert_eq!(serde_json::to_value(&settings).unwrap(), fixture);
+    }
+
+    #[test]
+    fn test_unversioned_history_is_kept() {
+        // Settings saved with a history but before the version field existed
+        let mut value: Value = serde_json::from_str(FIXTURES[1]).unwrap();
+        value.as_object_mut().unwrap().remove("version");
+        let settings: WorkoutSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(settings, load(FIXTURES[1]));
+    }
+
+    #[test]
+    fn test_migration_errors() {
+        assert!(migrate_settings(serde_json::json!([])).is_err());
+        assert!(migrate_settings(serde_json::json!({ "version": "2" })).is_err());
+        assert!(migrate_settings(serde_json::json!({ "version": 0 })).is_err());
+        assert!(migrate_settings(serde_json::json!({ "version": SETTINGS_VERSION + 1 })).is_err());
+        assert!(migrate_settings(serde_json::json!({ "routine_completions": { "Core": "yesterday" } })).is_err());
+
+        let err = serde_json::from_str::<WorkoutSettings>(r#"{"version": 99}"#).unwrap_err();
+        assert!(err.to_string().contains("newer"), "{}", err);
+    }
+}
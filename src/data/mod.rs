@@ -1 +1,3 @@
+pub mod backup;
+pub mod migrations;
 pub mod routines;
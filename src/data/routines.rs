@@ -1,4 +1,5 @@
 use crate::components::routine_card::Routine;
+use crate::components::settings::WorkoutSettings;
 
 pub fn get_routines() -> Vec<Routine> {
     vec![
@@ -6,51 +7,68 @@ pub fn get_routines() -> Vec<Routine> {
             id: "2".to_string(),
             name: "Upper Body".to_string(),
             exercises: vec![
-                "Push ups".to_string(),
-                "Overhead triceps".to_string(),
-                "Hammer curls".to_string(),
-                "Skull crushers".to_string(),
+                "Push ups".into(),
+                "Overhead triceps".into(),
+                "Hammer curls".into(),
+                "Skull crushers".into(),
             ],
+            ..Default::default()
         },
         Routine {
             id: "3".to_string(),
             name: "Core".to_string(),
             exercises: vec![
-                "Low plank".to_string(),
-                "Crunches".to_string(),
-                "Russian twists".to_string(),
-                "Bicycle crunches".to_string(),
+                "Low plank".into(),
+                "Crunches".into(),
+                "Russian twists".into(),
+                "Bicycle crunches".into(),
             ],
+            ..Default::default()
         },
         Routine {
             id: "4".to_string(),
             name: "Lower Body".to_string(),
             exercises: vec![
-                "Squats".to_string(),
-                "Lunges".to_string(),
-                "Calf raises".to_string(),
-                "Froggy glute lifts".to_string(),
+                "Squats".into(),
+                "Lunges".into(),
+                "Calf raises".into(),
+                "Froggy glute lifts".into(),
             ],
+            ..Default::default()
         },
         Routine {
             id: "5".to_string(),
             name: "Full Body".to_string(),
             exercises: vec![
-                "Mountain climbers".to_string(),
-                "Superman".to_string(),
-                "Inchworm".to_string(),
-                "Burpees".to_string(),
+                "Mountain climbers".into(),
+                "Superman".into(),
+                "Inchworm".into(),
+                "Burpees".into(),
             ],
+            ..Default::default()
         },
         Routine {
             id: "6".to_string(),
             name: "Mobility".to_string(),
             exercises: vec![
-                "Squat to Toe Touch".to_string(),
-                "Downward to Upward Dog".to_string(),
-                "Lunge and Reach".to_string(),
-                "90/90 Hip Stretch".to_string(),
+                "Squat to Toe Touch".into(),
+                "Downward to Upward Dog".into(),
+                "Lunge and Reach".into(),
+                "90/90 Hip Stretch".into(),
             ],
+            ..Default::default()
         },
     ]
 }
+
+/// Built-in routines followed by the user's custom routines.
+pub fn all_routines(settings: &WorkoutSettings) -> Vec<Routine> {
+    get_routines()
+        .into_iter()
+        .chain(settings.custom_routines.iter().cloned())
+        .collect()
+}
+
+pub fn find_routine(id: &str, settings: &WorkoutSettings) -> Option<Routine> {
+    all_routines(settings).into_iter().find(|r| r.id == id)
+}
@@ -2,9 +2,11 @@
 mod app;
 pub mod components;
 pub mod data;
+pub mod workout;
 
 pub use components::*;
 pub use data::*;
+pub use workout::*;
 
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
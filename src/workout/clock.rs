@@ -0,0 +1,153 @@
+use instant::Instant;
+use std::time::Duration;
+
+/// Wall-clock based workout timer.
+///
+/// Elapsed time is anchored to a monotonic instant taken when the clock was last
+/// started, plus the time accumulated before the last pause. Nothing depends on
+/// how often the clock is polled, so throttled browser timers only delay the
+/// next update; they never stretch the workout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorkoutClock {
+    running_since: Option<Instant>,
+    accumulated: Duration,
+}
+
+impl WorkoutClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Start or resume the clock. Does nothing if it is already running.
+    pub fn start(&mut self, now: Instant) {
+        if self.running_since.is_none() {
+            self.running_since = Some(now);
+        }
+    }
+
+    /// Pause the clock, keeping the time elapsed so far.
+    pub fn pause(&mut self, now: Instant) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated += now.saturating_duration_since(since);
+        }
+    }
+
+    /// Stop the clock and forget all elapsed time.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Jump to `elapsed`, keeping the clock running if it was.
+    pub fn seek(&mut self, elapsed: Duration, now: Instant) {
+        self.accumulated = elapsed;
+        if self.running_since.is_some() {
+            self.running_since = Some(now);
+        }
+    }
+
+    /// Total running time as of `now`.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        match self.running_since {
+            Some(since) => self.accumulated + now.saturating_duration_since(since),
+            None => self.accumulated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn test_new_clock_is_stopped_at_zero() {
+        let clock = WorkoutClock::new();
+        assert!(!clock.is_running());
+        assert_eq!(clock.elapsed(Instant::now()), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_elapsed_follows_wall_clock() {
+        let t0 = Instant::now();
+        let mut clock = WorkoutClock::new();
+        clock.start(t0);
+
+        // A single late poll catches up on all the time that passed
+        assert_eq!(clock.elapsed(t0 + secs(95)), secs(95));
+    }
+
+    #[test]
+    fn test_pause_excludes_paused_time() {
+        let t0 = Instant::now();
+        let mut clock = WorkoutClock::new();
+        clock.start(t0);
+        clock.pause(t0 + secs(10));
+        assert_eq!(clock.elapsed(t0 + secs(60)), secs(10));
+
+        clock.start(t0 + secs(60));
+        assert_eq!(clock.elapsed(t0 + secs(65)), secs(15));
+    }
+
+    #[test]
+    fn test_start_and_pause_are_idempotent() {
+        let t0 = Instant::now();
+        let mut clock = WorkoutClock::new();
+        clock.start(t0);
+        clock.start(t0 + secs(5));
+        assert_eq!(clock.elapsed(t0 + secs(10)), secs(10));
+
+        clock.pause(t0 + secs(10));
+        clock.pause(t0 + secs(20));
+        assert_eq!(clock.elapsed(t0 + secs(30)), secs(10));
+    }
+
+    #[test]
+    fn test_reset() {
+        let t0 = Instant::now();
+        let mut clock = WorkoutClock::new();
+        clock.start(t0);
+        clock.reset();
+        assert!(!clock.is_running());
+        assert_eq!(clock.elapsed(t0 + secs(10)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_seek() {
+        let t0 = Instant::now();
+        let mut clock = WorkoutClock::new();
+        clock.seek(secs(40), t0);
+        assert!(!clock.is_running());
+        assert_eq!(clock.elapsed(t0 + secs(10)), secs(40));
+
+        clock.start(t0 + secs(10));
+        clock.seek(secs(5), t0 + secs(20));
+        assert!(clock.is_running());
+        assert_eq!(clock.elapsed(t0 + secs(25)), secs(10));
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn prop_elapsed_is_sum_of_running_segments(segments: Vec<(u16, u16)>) -> bool {
+        // Alternate running and paused periods of arbitrary length (in ms)
+        let t0 = Instant::now();
+        let mut now = t0;
+        let mut clock = WorkoutClock::new();
+        let mut expected = Duration::ZERO;
+
+        for (running, paused) in segments {
+            clock.start(now);
+            now += Duration::from_millis(running as u64);
+            expected += Duration::from_millis(running as u64);
+            clock.pause(now);
+            now += Duration::from_millis(paused as u64);
+        }
+
+        clock.elapsed(now) == expected
+    }
+}
@@ -0,0 +1,424 @@
+use std::time::Duration;
+
+use crate::components::settings::WorkoutSettings;
+use crate::workout::schedule::{Schedule, Side};
+
+/// How long before the end of a stage the "three two one" cue starts.
+pub const COUNTDOWN: Duration = Duration::from_millis(2500);
+
+/// Rests shorter than this are too busy for a "Next up" announcement.
+const NEXT_UP_MIN_REST: Duration = Duration::from_secs(10);
+
+/// Work stages shorter than this only get their name and the countdown.
+const MID_STAGE_CUE_MIN: Duration = Duration::from_secs(20);
+
+/// How long before the end of a work stage the "ten seconds" cue is spoken.
+const TEN_SECONDS: Duration = Duration::from_secs(10);
+
+/// Optional cues, as chosen in the settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CueOptions {
+    pub halfway: bool,
+    pub ten_seconds: bool,
+}
+
+impl CueOptions {
+    pub fn from_settings(settings: &WorkoutSettings) -> Self {
+        CueOptions {
+            halfway: settings.halfway_cue,
+            ten_seconds: settings.ten_seconds_cue,
+        }
+    }
+}
+
+/// Something to say `at` a point in the workout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cue {
+    pub at: Duration,
+    /// Stage the cue belongs to; one past the last stage for the closing cue.
+    pub stage: usize,
+    pub text: String,
+}
+
+/// Every cue of a workout, worked out up front so the timer only has to ask
+/// which of them fall between two ticks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CuePlan {
+    cues: Vec<Cue>,
+}
+
+impl CuePlan {
+    pub fn new(schedule: &Schedule, options: CueOptions) -> Self {
+        let stages = schedule.stages();
+        let mut cues = Vec::new();
+        let mut start = Duration::ZERO;
+        let mut last_work: Option<&str> = None;
+        for (index, stage) in stages.iter().enumerate() {
+            let end = start + stage.duration;
+            // Zero-length stages are never shown, so they get no cues
+            if stage.duration.is_zero() {
+                continue;
+            }
+            let next = stages[index + 1..].iter().find(|s| !s.duration.is_zero());
+            let mut cue = |at: Duration, text: String| cues.push(Cue { at, stage: index, text });
+
+            // Announce the stage name, and start the clock on every EMOM minute
+            if stage.side == Some(Side::Right) {
+                cue(start, "Switch sides".to_string());
+            } else {
+                cue(start, stage.label.clone());
+            }
+            if stage.emom {
+                cue(start, "Go".to_string());
+            }
+
+            // Mid-stage cues keep long work stages from going quiet. A one-sided
+            // exercise gets them once, over both sides, where "Switch sides" is
+            // already its halfway point.
+            let exercise = match stage.side {
+                None => Some(stage.duration),
+                Some(Side::Left) => None,
+                Some(Side::Right) => Some(stages[index - 1].duration + stage.duration),
+            };
+            if let Some(duration) = exercise.filter(|&d| stage.is_high_intensity && d >= MID_STAGE_CUE_MIN) {
+                if options.halfway && stage.side.is_none() {
+                    cue(start + duration / 2, "Halfway".to_string());
+                }
+                // At exactly twice the cue time this would land on "halfway"
+                if options.ten_seconds && duration > MID_STAGE_CUE_MIN {
+                    cue(end.saturating_sub(TEN_SECONDS).max(start), "Ten seconds".to_string());
+                }
+            }
+
+            // Halfway through a rest, say what is coming so the user can get ready,
+            // unless it's another round of what they just did
+            let is_rest = !stage.is_high_intensity && stage.phase.is_none();
+            if let Some(next) = next.filter(|next| next.is_high_intensity) {
+                if is_rest && stage.duration >= NEXT_UP_MIN_REST && last_work != Some(next.label.as_str()) {
+                    cue(start + stage.duration / 2, format!("Next up: {}", next.label));
+                }
+            }
+
+            // Count down to the end of the stage, but not halfway through a
+            // one-sided exercise
+            if !next.is_some_and(|next| next.side == Some(Side::Right)) {
+                cue(end.saturating_sub(COUNTDOWN).max(start), "three two one".to_string());
+            }
+
+            if stage.is_high_intensity {
+                last_work = Some(&stage.label);
+            }
+            start = end;
+        }
+        cues.push(Cue {
+            at: schedule.duration(),
+            stage: stages.len(),
+            text: "Workout Complete".to_string(),
+        });
+
+        // Cues of a stage are added in the order they should play on a tie
+        cues.sort_by_key(|cue| cue.at);
+        CuePlan { cues }
+    }
+
+    pub fn cues(&self) -> &[Cue] {
+        &self.cues
+    }
+
+    /// Cues due from `from` up to but not including `to`. Consecutive windows
+    /// return every cue exactly once, however far apart the ticks are.
+    pub fn between(&self, from: Duration, to: Duration) -> &[Cue] {
+        let first = self.cues.partition_point(|cue| cue.at < from);
+        let last = self.cues.partition_point(|cue| cue.at < to);
+        &self.cues[first..last.max(first)]
+    }
+}
+
+/// Number shown by the countdown overlay with `remaining` time left in the stage.
+pub fn countdown_digit(remaining: Duration) -> Option<u32> {
+    (!remaining.is_zero() && remaining <= COUNTDOWN).then(|| remaining.as_secs_f64().ceil() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::routines::all_routines;
+    use crate::workout::schedule::tests::{routine, settings};
+    use crate::workout::schedule::WorkoutMode;
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+
+    fn timeline(plan: &CuePlan) -> Vec<(u64, &str)> {
+        plan.cues()
+            .iter()
+            .map(|cue| (cue.at.as_millis() as u64, cue.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_standard_cues() {
+        let schedule = Schedule::new(&routine(&["Squats", "Burpees"]), &settings(30, 15, 45, 1));
+        let plan = CuePlan::new(&schedule, CueOptions::default());
+        assert_eq!(
+            timeline(&plan),
+            [
+                (0, "Prepare"),
+                (5_000, "Next up: Squats"),
+                (7_500, "three two one"),
+                (10_000, "Squats"),
+                (37_500, "three two one"),
+                (40_000, "Rest"),
+                (47_500, "Next up: Burpees"),
+                (52_500, "three two one"),
+                (55_000, "Burpees"),
+                (82_500, "three two one"),
+                (85_000, "Workout Complete"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mid_stage_cues() {
+        let options = CueOptions {
+            halfway: true,
+            ten_seconds: true,
+        };
+        // Stages: Prepare 0-10, Squats 10-70, Rest 70-75, Burpees 75-95
+        let mut routine = routine(&["Squats", "Burpees"]);
+        routine.exercises[1].duration_secs = Some(20);
+        let schedule = Schedule::new(&routine, &settings(60, 5, 45, 1));
+        let mut plan = CuePlan::new(&schedule, options);
+        plan.cues
+            .retain(|cue| cue.text == "Halfway" || cue.text == "Ten seconds");
+        // A 20s stage only gets "halfway", which would coincide with "ten seconds"
+        assert_eq!(
+            timeline(&plan),
+            [(40_000, "Halfway"), (60_000, "Ten seconds"), (85_000, "Halfway")]
+        );
+    }
+
+    #[test]
+    fn test_one_sided_mid_stage_cues() {
+        let options = CueOptions {
+            halfway: true,
+            ten_seconds: true,
+        };
+        // Lunges 10-30 on the left and 30-50 on the right
+        let schedule = Schedule::new(&routine(&["Lunges"]), &settings(40, 15, 45, 1));
+        let plan = CuePlan::new(&schedule, options);
+        assert_eq!(
+            timeline(&plan)[3..],
+            [
+                (10_000, "Lunges"),
+                (30_000, "Switch sides"),
+                (40_000, "Ten seconds"),
+                (47_500, "three two one"),
+                (50_000, "Workout Complete"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_switch_sides_and_emom_cues() {
+        let schedule = Schedule::new(&routine(&["Lunges"]), &settings(30, 15, 45, 1));
+        let plan = CuePlan::new(&schedule, CueOptions::default());
+        assert_eq!(
+            timeline(&plan)[3..],
+            [
+                (10_000, "Lunges"),
+                (25_000, "Switch sides"),
+                (37_500, "three two one"),
+                (40_000, "Workout Complete"),
+            ]
+        );
+
+        // Every EMOM minute says what to do and then "Go", with or without a rep target
+        let mut emom = routine(&["Burpees", "Squats"]);
+        emom.overrides.mode = Some(WorkoutMode::Emom);
+        emom.exercises[0].reps = Some(12);
+        let plan = CuePlan::new(&Schedule::new(&emom, &settings(30, 15, 45, 2)), CueOptions::default());
+        let starts: Vec<(u64, &str)> = timeline(&plan)
+            .into_iter()
+            .filter(|(at, text)| at % 60_000 == 10_000 && !text.starts_with("Next up"))
+            .collect();
+        assert_eq!(
+            starts,
+            [
+                (10_000, "12 Burpees"),
+                (10_000, "Go"),
+                (70_000, "Squats"),
+                (70_000, "Go"),
+                (130_000, "12 Burpees"),
+                (130_000, "Go"),
+                (190_000, "Squats"),
+                (190_000, "Go"),
+                (250_000, "Workout Complete"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_up_skips_repeats() {
+        // Tabata rests are 10s, but only the block break leads to a new exercise
+        let mut tabata = routine(&["Burpees", "Squats"]);
+        tabata.overrides.mode = Some(WorkoutMode::Tabata);
+        let plan = CuePlan::new(&Schedule::new(&tabata, &settings(30, 15, 45, 1)), CueOptions::default());
+        let next_up: Vec<(u64, &str)> = timeline(&plan)
+            .into_iter()
+            .filter(|(_, text)| text.starts_with("Next up"))
+            .collect();
+        assert_eq!(
+            next_up,
+            [(5_000, "Next up: Burpees"), ((10 + 230 + 30) * 1000, "Next up: Squats")]
+        );
+    }
+
+    #[test]
+    fn test_between() {
+        let schedule = Schedule::new(&routine(&["Squats"]), &settings(30, 15, 45, 1));
+        let plan = CuePlan::new(&schedule, CueOptions::default());
+        let secs = Duration::from_secs;
+        let texts = |from, to| -> Vec<&str> { plan.between(from, to).iter().map(|c| c.text.as_str()).collect() };
+
+        assert_eq!(texts(secs(0), Duration::from_millis(25)), ["Prepare"]);
+        assert_eq!(
+            texts(Duration::from_millis(25), secs(9)),
+            ["Next up: Squats", "three two one"]
+        );
+        // Landing exactly on a stage start announces it
+        assert_eq!(texts(secs(10), secs(11)), ["Squats"]);
+        // Nothing before the end of the workout, and a window going backwards is empty
+        assert!(texts(secs(38), secs(40)).is_empty());
+        assert!(texts(secs(20), secs(10)).is_empty());
+        assert_eq!(texts(secs(40), secs(41)), ["Workout Complete"]);
+    }
+
+    #[test]
+    fn test_countdown_digit() {
+        let millis = Duration::from_millis;
+        assert_eq!(countdown_digit(millis(3000)), None);
+        assert_eq!(countdown_digit(millis(2500)), Some(3));
+        assert_eq!(countdown_digit(millis(1500)), Some(2));
+        assert_eq!(countdown_digit(millis(1000)), Some(1));
+        assert_eq!(countdown_digit(millis(1)), Some(1));
+        assert_eq!(countdown_digit(Duration::ZERO), None);
+    }
+
+    /// A routine's cue plan and a set of tick times across the whole workout.
+    #[derive(Clone, Debug)]
+    struct Ticks {
+        plan: CuePlan,
+        stages: usize,
+        duration: Duration,
+        ticks: Vec<Duration>,
+    }
+
+    impl Arbitrary for Ticks {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let settings = WorkoutSettings::arbitrary(g).clamped();
+            let routines = all_routines(&settings);
+            let routine = g.choose(&routines).unwrap();
+            let schedule = Schedule::new(routine, &settings);
+            let options = CueOptions {
+                halfway: bool::arbitrary(g),
+                ten_seconds: bool::arbitrary(g),
+            };
+
+            // Random tick times, from a few milliseconds to minutes apart, running past the end
+            let end = schedule.duration().as_millis() as u64 + 1_000;
+            let mut ticks: Vec<Duration> = (0..usize::arbitrary(g) % 40)
+                .map(|_| Duration::from_millis(u64::arbitrary(g) % end))
+                .chain([Duration::ZERO, Duration::from_millis(end)])
+                .collect();
+            ticks.sort();
+            ticks.dedup();
+
+            Ticks {
+                plan: CuePlan::new(&schedule, options),
+                stages: schedule.stages().len(),
+                duration: schedule.duration(),
+                ticks,
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn prop_every_cue_fires_once(input: Ticks) -> bool {
+        let fired: Vec<&Cue> = input
+            .ticks
+            .windows(2)
+            .flat_map(|window| input.plan.between(window[0], window[1]))
+            .collect();
+        fired.len() == input.plan.cues().len() && fired.iter().zip(input.plan.cues()).all(|(a, b)| *a == b)
+    }
+
+    /// Ticks up to some point, then a jump to `target` and ticks from there on, as
+    /// when skipping stages or resuming a saved session.
+    #[derive(Clone, Debug)]
+    struct Seek {
+        plan: CuePlan,
+        before: Vec<Duration>,
+        target: Duration,
+        after: Vec<Duration>,
+    }
+
+    impl Arbitrary for Seek {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let Ticks { plan, ticks, .. } = Ticks::arbitrary(g);
+            let end = *ticks.last().unwrap();
+            // Seeks land on stage starts, which is where cues are, and resumes anywhere
+            let target = match bool::arbitrary(g) {
+                true => g.choose(plan.cues()).unwrap().at,
+                false => Duration::from_millis(u64::arbitrary(g) % end.as_millis() as u64),
+            };
+            // Resuming a session is a seek before the first tick
+            let before = ticks[..usize::arbitrary(g) % ticks.len()].to_vec();
+            // The last tick is past the end, so the rest of the workout is covered
+            let after = std::iter::once(target)
+                .chain(ticks.into_iter().filter(|&t| t > target))
+                .collect::<Vec<_>>();
+            Seek {
+                plan,
+                before,
+                target,
+                after,
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn prop_seek_picks_up_cues_from_the_target(input: Seek) -> bool {
+        // The timer speaks from where cues were last spoken up to each tick, and a
+        // seek moves that point to the target, forwards or backwards
+        fn tick<'a>(plan: &'a CuePlan, spoken_until: &mut Duration, now: Duration) -> &'a [Cue] {
+            let cues = plan.between(*spoken_until, now);
+            *spoken_until = now.max(*spoken_until);
+            cues
+        }
+        let mut spoken_until = Duration::ZERO;
+        for &now in &input.before {
+            tick(&input.plan, &mut spoken_until, now);
+        }
+        spoken_until = input.target;
+        let fired: Vec<&Cue> = input
+            .after
+            .iter()
+            .flat_map(|&now| tick(&input.plan, &mut spoken_until, now))
+            .collect();
+
+        // Everything from the target on fires once, including a cue right at it,
+        // and nothing skipped over does
+        let expected: Vec<&Cue> = input.plan.cues().iter().filter(|cue| cue.at >= input.target).collect();
+        fired == expected
+    }
+
+    #[quickcheck]
+    fn prop_cues_are_ordered_and_in_range(input: Ticks) -> bool {
+        let cues = input.plan.cues();
+        cues.windows(2).all(|pair| pair[0].at <= pair[1].at)
+            && cues
+                .iter()
+                .all(|cue| cue.at <= input.duration && cue.stage <= input.stages)
+            && cues.last().is_some_and(|cue| cue.text == "Workout Complete")
+    }
+}
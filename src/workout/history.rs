@@ -0,0 +1,568 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+use crate::components::routine_card::Routine;
+use crate::components::settings::WorkoutSettings;
+use crate::data::routines::all_routines;
+use crate::workout::schedule::{Schedule, WorkoutTiming};
+
+/// One workout session, finished or abandoned part way through.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WorkoutRecord {
+    pub routine_id: String,
+    pub routine_name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Timing the routine was run with.
+    pub timing: WorkoutTiming,
+    pub stages_completed: usize,
+    pub total_stages: usize,
+    /// Seconds of high-intensity work done.
+    pub work_secs: u64,
+    pub finished: bool,
+    /// Rounds tapped during AMRAP stages, in stage order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rounds: Vec<StageRounds>,
+}
+
+/// Rounds logged during one AMRAP stage of a workout.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StageRounds {
+    /// Index of the stage in the workout's schedule.
+    pub stage: usize,
+    pub label: String,
+    pub rounds: u32,
+}
+
+/// Log one more round for `stage`, keeping `rounds` in stage order.
+pub fn tap_round(rounds: &mut Vec<StageRounds>, stage: usize, label: &str) {
+    match rounds.binary_search_by_key(&stage, |r| r.stage) {
+        Ok(index) => rounds[index].rounds += 1,
+        Err(index) => rounds.insert(
+            index,
+            StageRounds {
+                stage,
+                label: label.to_string(),
+                rounds: 1,
+            },
+        ),
+    }
+}
+
+impl WorkoutRecord {
+    /// Record a session of `routine` that stopped `elapsed` into its schedule.
+    pub fn new(
+        routine: &Routine,
+        settings: &WorkoutSettings,
+        started_at: DateTime<Utc>,
+        ended_at: DateTime<Utc>,
+        elapsed: Duration,
+    ) -> Self {
+        let schedule = Schedule::new(routine, settings);
+        // Stopping during the cool-down still counts as finishing the workout
+        let total_stages = schedule.main_stage_count();
+        WorkoutRecord {
+            routine_id: routine.id.clone(),
+            routine_name: routine.name.clone(),
+            started_at,
+            ended_at,
+            timing: WorkoutTiming::resolve(routine, settings),
+            stages_completed: schedule.stages_completed(elapsed).min(total_stages),
+            total_stages,
+            work_secs: schedule.work_time_until(elapsed).as_secs(),
+            finished: elapsed >= schedule.stage_start(total_stages),
+            rounds: Vec::new(),
+        }
+    }
+
+    /// Rounds tapped across all AMRAP stages.
+    pub fn total_rounds(&self) -> u32 {
+        self.rounds.iter().map(|r| r.rounds).sum()
+    }
+
+    /// Record a finished session of `routine` that ended at `ended_at`.
+    pub fn completed(routine: &Routine, settings: &WorkoutSettings, ended_at: DateTime<Utc>) -> Self {
+        let duration = Schedule::new(routine, settings).duration();
+        let started_at = ended_at - chrono::Duration::from_std(duration).unwrap_or_default();
+        Self::new(routine, settings, started_at, ended_at, duration)
+    }
+}
+
+/// Convert the old last-completion-by-routine-name map into history records.
+///
+/// Names are matched against the known routines; anything that no longer
+/// exists keeps its name as the id so the completion isn't lost.
+pub fn migrate_completions(
+    completions: &HashMap<String, DateTime<Utc>>,
+    settings: &WorkoutSettings,
+) -> Vec<WorkoutRecord> {
+    let routines = all_routines(settings);
+    let mut history: Vec<WorkoutRecord> = completions
+        .iter()
+        .map(|(name, completed_at)| {
+            let routine = routines
+                .iter()
+                .find(|r| &r.name == name)
+                .cloned()
+                .unwrap_or_else(|| Routine {
+                    id: name.clone(),
+                    name: name.clone(),
+                    ..Default::default()
+                });
+            WorkoutRecord::completed(&routine, settings, *completed_at)
+        })
+        .collect();
+    history.sort_by_key(|record| record.ended_at);
+    history
+}
+
+/// When `routine_id` was last finished, if ever.
+pub fn last_completion(history: &[WorkoutRecord], routine_id: &str) -> Option<DateTime<Utc>> {
+    history
+        .iter()
+        .filter(|record| record.finished && record.routine_id == routine_id)
+        .map(|record| record.ended_at)
+        .max()
+}
+
+/// Monday of the week `date` falls in.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Finished workouts per week, keyed by the local Monday of each week.
+fn weekly_counts(history: &[WorkoutRecord], now: DateTime<FixedOffset>) -> HashMap<NaiveDate, u32> {
+    let mut counts = HashMap::new();
+    for record in history.iter().filter(|record| record.finished) {
+        let date = record.ended_at.with_timezone(now.offset()).date_naive();
+        *counts.entry(week_start(date)).or_default() += 1;
+    }
+    counts
+}
+
+/// Finished workouts so far in the local week containing `now`.
+pub fn workouts_this_week(history: &[WorkoutRecord], now: DateTime<FixedOffset>) -> u32 {
+    weekly_counts(history, now)
+        .get(&week_start(now.date_naive()))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Consecutive weeks, up to now, with at least `goal` finished workouts.
+///
+/// The current week only counts once its goal is met, so an unfinished week
+/// doesn't break a streak that is still going.
+pub fn goal_streak(history: &[WorkoutRecord], goal: u32, now: DateTime<FixedOffset>) -> u32 {
+    let counts = weekly_counts(history, now);
+    let met = |week: &NaiveDate| counts.get(week).is_some_and(|&count| count >= goal.max(1));
+
+    let mut week = week_start(now.date_naive());
+    if !met(&week) {
+        week -= chrono::Duration::weeks(1);
+    }
+    let mut streak = 0;
+    while met(&week) {
+        streak += 1;
+        week -= chrono::Duration::weeks(1);
+    }
+    streak
+}
+
+/// High-intensity seconds of finished workouts per local calendar day.
+pub fn daily_work_secs(history: &[WorkoutRecord], offset: FixedOffset) -> HashMap<NaiveDate, u64> {
+    let mut days = HashMap::new();
+    for record in history.iter().filter(|record| record.finished) {
+        let date = record.ended_at.with_timezone(&offset).date_naive();
+        *days.entry(date).or_default() += record.work_secs;
+    }
+    days
+}
+
+/// Heatmap shade from 0 (no work) to 4 for a day's high-intensity seconds.
+pub fn heat_level(work_secs: u64) -> u8 {
+    match work_secs {
+        0 => 0,
+        1..300 => 1,
+        300..600 => 2,
+        600..1200 => 3,
+        _ => 4,
+    }
+}
+
+/// Totals shown on the history page.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistoryStats {
+    /// Finished workouts.
+    pub workouts: usize,
+    /// Average finished workouts per week since the first one.
+    pub workouts_per_week: f64,
+    /// High-intensity seconds across all sessions, finished or not.
+    pub work_secs: u64,
+    /// Finished workouts per routine as `(name, count)`, most frequent first.
+    pub routine_counts: Vec<(String, usize)>,
+    /// Most days between two consecutive days with a finished workout.
+    pub longest_gap_days: Option<i64>,
+}
+
+impl HistoryStats {
+    /// Compute stats using the calendar days of the timezone `now` is in.
+    pub fn new(history: &[WorkoutRecord], now: DateTime<FixedOffset>) -> Self {
+        let finished: Vec<&WorkoutRecord> = history.iter().filter(|record| record.finished).collect();
+        let days: BTreeSet<NaiveDate> = finished
+            .iter()
+            .map(|record| record.ended_at.with_timezone(now.offset()).date_naive())
+            .collect();
+
+        let workouts_per_week = match days.first() {
+            Some(first) => {
+                let weeks = now.date_naive().signed_duration_since(*first).num_days().max(0) / 7 + 1;
+                finished.len() as f64 / weeks as f64
+            }
+            None => 0.0,
+        };
+
+        // Count by id, but show the most recent name the routine was run under
+        let mut counts: HashMap<&str, (String, usize)> = HashMap::new();
+        for record in &finished {
+            let entry = counts.entry(&record.routine_id).or_default();
+            entry.0 = record.routine_name.clone();
+            entry.1 += 1;
+        }
+        let mut routine_counts: Vec<(String, usize)> = counts.into_values().collect();
+        routine_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let longest_gap_days = days
+            .iter()
+            .zip(days.iter().skip(1))
+            .map(|(a, b)| b.signed_duration_since(*a).num_days())
+            .max();
+
+        HistoryStats {
+            workouts: finished.len(),
+            workouts_per_week,
+            work_secs: history.iter().map(|record| record.work_secs).sum(),
+            routine_counts,
+            longest_gap_days,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use quickcheck::{Arbitrary, Gen};
+
+    fn routine() -> Routine {
+        Routine {
+            id: "4".to_string(),
+            name: "Lower Body".to_string(),
+            exercises: vec!["Squats".into(), "Calf Raises".into()],
+            ..Default::default()
+        }
+    }
+
+    fn settings() -> WorkoutSettings {
+        WorkoutSettings {
+            high_intensity_duration_secs: 30,
+            rest_exercise_duration_secs: 15,
+            rest_set_duration_secs: 45,
+            sets: 1,
+            ..WorkoutSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_record_finished_workout() {
+        let start = Utc.with_ymd_and_hms(2023, 5, 15, 10, 0, 0).unwrap();
+        let end = start + chrono::Duration::seconds(85);
+        let record = WorkoutRecord::new(&routine(), &settings(), start, end, Duration::from_secs(85));
+
+        assert!(record.finished);
+        assert_eq!(record.stages_completed, 4);
+        assert_eq!(record.total_stages, 4);
+        assert_eq!(record.work_secs, 60);
+        assert_eq!(record.timing.high_intensity_duration_secs, 30);
+        assert_eq!(record.routine_name, "Lower Body");
+    }
+
+    #[test]
+    fn test_record_abandoned_workout() {
+        let start = Utc.with_ymd_and_hms(2023, 5, 15, 10, 0, 0).unwrap();
+        let end = start + chrono::Duration::seconds(45);
+        let record = WorkoutRecord::new(&routine(), &settings(), start, end, Duration::from_secs(45));
+
+        assert!(!record.finished);
+        assert_eq!(record.stages_completed, 2);
+        assert_eq!(record.work_secs, 30);
+    }
+
+    #[test]
+    fn test_cool_down_is_optional() {
+        let settings = WorkoutSettings {
+            warm_up_secs: 60,
+            cool_down_secs: 90,
+            ..settings()
+        };
+        let start = Utc.with_ymd_and_hms(2023, 5, 15, 10, 0, 0).unwrap();
+        let record_at = |secs: i64| {
+            let end = start + chrono::Duration::seconds(secs);
+            WorkoutRecord::new(&routine(), &settings, start, end, Duration::from_secs(secs as u64))
+        };
+
+        // Prepare, two warm-up stages, then the workout ends at 145s
+        let stopped_in_warm_up = record_at(30);
+        assert!(!stopped_in_warm_up.finished);
+        assert_eq!(stopped_in_warm_up.total_stages, 6);
+
+        let stopped_in_cool_down = record_at(150);
+        assert!(stopped_in_cool_down.finished);
+        assert_eq!(stopped_in_cool_down.stages_completed, 6);
+        assert_eq!(stopped_in_cool_down.total_stages, 6);
+        assert!(record_at(145).finished);
+        assert!(!record_at(144).finished);
+    }
+
+    #[test]
+    fn test_tap_rounds() {
+        let mut rounds = Vec::new();
+        tap_round(&mut rounds, 3, "Lunges");
+        tap_round(&mut rounds, 1, "Squats");
+        tap_round(&mut rounds, 3, "Lunges");
+        assert_eq!(
+            rounds.iter().map(|r| (r.stage, r.rounds)).collect::<Vec<_>>(),
+            [(1, 1), (3, 2)]
+        );
+
+        let start = Utc.with_ymd_and_hms(2023, 5, 15, 10, 0, 0).unwrap();
+        let record = WorkoutRecord {
+            rounds,
+            ..WorkoutRecord::completed(&routine(), &settings(), start)
+        };
+        assert_eq!(record.total_rounds(), 3);
+
+        // Records without rounds are stored as before
+        let json = serde_json::to_value(WorkoutRecord::completed(&routine(), &settings(), start)).unwrap();
+        assert!(json.get("rounds").is_none());
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(serde_json::from_str::<WorkoutRecord>(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn test_last_completion_ignores_abandoned_and_other_routines() {
+        let day = |d| Utc.with_ymd_and_hms(2023, 5, d, 10, 0, 0).unwrap();
+        let record = |id: &str, d, elapsed| {
+            let routine = Routine {
+                id: id.to_string(),
+                ..routine()
+            };
+            WorkoutRecord::new(&routine, &settings(), day(d), day(d), Duration::from_secs(elapsed))
+        };
+        let history = vec![
+            record("4", 1, 85),
+            record("4", 3, 45),
+            record("5", 4, 85),
+            record("4", 2, 85),
+        ];
+
+        assert_eq!(last_completion(&history, "4"), Some(day(2)));
+        assert_eq!(last_completion(&history, "5"), Some(day(4)));
+        assert_eq!(last_completion(&history, "6"), None);
+    }
+
+    #[test]
+    fn test_migrate_completions() {
+        let day = |d| Utc.with_ymd_and_hms(2023, 5, d, 10, 0, 0).unwrap();
+        let completions = HashMap::from([
+            ("Core".to_string(), day(3)),
+            ("Lower Body".to_string(), day(1)),
+            ("Retired".to_string(), day(2)),
+        ]);
+        let history = migrate_completions(&completions, &settings());
+
+        let ids: Vec<_> = history.iter().map(|r| r.routine_id.as_str()).collect();
+        assert_eq!(ids, ["4", "Retired", "3"]);
+        assert!(history.iter().all(|r| r.finished));
+        assert_eq!(last_completion(&history, "3"), Some(day(3)));
+        assert_eq!(history[0].work_secs, 120);
+    }
+
+    #[test]
+    fn test_history_stats() {
+        let day = |d| Utc.with_ymd_and_hms(2023, 5, d, 10, 0, 0).unwrap();
+        let record = |id: &str, name: &str, d, elapsed| {
+            let routine = Routine {
+                id: id.to_string(),
+                name: name.to_string(),
+                ..routine()
+            };
+            WorkoutRecord::new(&routine, &settings(), day(d), day(d), Duration::from_secs(elapsed))
+        };
+        let history = vec![
+            record("4", "Legs", 1, 85),
+            record("4", "Lower Body", 2, 85),
+            record("5", "Full Body", 2, 85),
+            record("5", "Full Body", 3, 45),
+            record("3", "Core", 9, 85),
+        ];
+        let now = day(14).fixed_offset();
+        let stats = HistoryStats::new(&history, now);
+
+        assert_eq!(stats.workouts, 4);
+        assert_eq!(stats.workouts_per_week, 2.0);
+        assert_eq!(stats.work_secs, 4 * 60 + 30);
+        assert_eq!(
+            stats.routine_counts,
+            vec![
+                ("Lower Body".to_string(), 2),
+                ("Core".to_string(), 1),
+                ("Full Body".to_string(), 1)
+            ]
+        );
+        assert_eq!(stats.longest_gap_days, Some(7));
+    }
+
+    fn finished_at(ended_at: DateTime<Utc>) -> WorkoutRecord {
+        WorkoutRecord::completed(&routine(), &settings(), ended_at)
+    }
+
+    #[test]
+    fn test_goal_streak() {
+        // 2023-05-15 is a Monday
+        let day = |d| Utc.with_ymd_and_hms(2023, 5, d, 12, 0, 0).unwrap();
+        let history: Vec<_> = [1, 3, 8, 10, 15].into_iter().map(|d| finished_at(day(d))).collect();
+
+        // This week isn't done yet, so the two previous weeks count
+        let now = day(16).fixed_offset();
+        assert_eq!(workouts_this_week(&history, now), 1);
+        assert_eq!(goal_streak(&history, 2, now), 2);
+        assert_eq!(goal_streak(&history, 1, now), 3);
+        assert_eq!(goal_streak(&history, 3, now), 0);
+
+        // A week without workouts breaks the streak
+        let now = day(29).fixed_offset();
+        assert_eq!(workouts_this_week(&history, now), 0);
+        assert_eq!(goal_streak(&history, 1, now), 0);
+    }
+
+    #[test]
+    fn test_goal_streak_uses_local_week() {
+        // Sunday evening in New York is already Monday in UTC
+        let new_york = FixedOffset::west_opt(4 * 3600).unwrap();
+        let sunday_evening = Utc.with_ymd_and_hms(2023, 5, 15, 1, 0, 0).unwrap();
+        let history = vec![finished_at(sunday_evening)];
+
+        let monday = new_york.with_ymd_and_hms(2023, 5, 15, 9, 0, 0).unwrap();
+        assert_eq!(workouts_this_week(&history, monday), 0);
+        assert_eq!(goal_streak(&history, 1, monday), 1);
+
+        let utc_monday = Utc.with_ymd_and_hms(2023, 5, 15, 9, 0, 0).unwrap().fixed_offset();
+        assert_eq!(workouts_this_week(&history, utc_monday), 1);
+    }
+
+    /// Finished workouts at arbitrary times within ~10 weeks of `now`, in an arbitrary timezone.
+    #[derive(Clone, Debug)]
+    struct StreakInput {
+        history: Vec<WorkoutRecord>,
+        goal: u32,
+        now: DateTime<FixedOffset>,
+    }
+
+    impl Arbitrary for StreakInput {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let offset = FixedOffset::east_opt(i32::arbitrary(g) % (14 * 3600)).unwrap();
+            let now = (Utc.with_ymd_and_hms(2023, 5, 15, 0, 0, 0).unwrap()
+                + chrono::Duration::seconds(u32::arbitrary(g) as i64 % (7 * 86400)))
+            .with_timezone(&offset);
+            let history = (0..usize::arbitrary(g) % 30)
+                .map(|_| {
+                    let secs_ago = u32::arbitrary(g) as i64 % (70 * 86400);
+                    finished_at(now.with_timezone(&Utc) - chrono::Duration::seconds(secs_ago))
+                })
+                .collect();
+            StreakInput {
+                history,
+                goal: u32::arbitrary(g) % 5 + 1,
+                now,
+            }
+        }
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn prop_streak_needs_enough_workouts(input: StreakInput) -> bool {
+        let streak = goal_streak(&input.history, input.goal, input.now);
+        // Each counted week has at least `goal` workouts
+        streak * input.goal <= input.history.len() as u32
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn prop_streak_ignores_order(input: StreakInput) -> bool {
+        let mut reversed = input.history.clone();
+        reversed.reverse();
+        goal_streak(&input.history, input.goal, input.now) == goal_streak(&reversed, input.goal, input.now)
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn prop_extra_workout_never_breaks_streak(input: StreakInput) -> bool {
+        let streak = goal_streak(&input.history, input.goal, input.now);
+        let mut history = input.history.clone();
+        history.push(finished_at(input.now.with_timezone(&Utc)));
+        workouts_this_week(&history, input.now) == workouts_this_week(&input.history, input.now) + 1
+            && goal_streak(&history, input.goal, input.now) >= streak
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn prop_abandoned_workouts_dont_count(input: StreakInput) -> bool {
+        let abandoned: Vec<_> = input
+            .history
+            .iter()
+            .map(|record| WorkoutRecord {
+                finished: false,
+                ..record.clone()
+            })
+            .collect();
+        goal_streak(&abandoned, input.goal, input.now) == 0 && workouts_this_week(&abandoned, input.now) == 0
+    }
+
+    #[test]
+    fn test_daily_work_secs() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let day = |d, h| Utc.with_ymd_and_hms(2023, 5, d, h, 0, 0).unwrap();
+        let mut history = vec![finished_at(day(1, 9)), finished_at(day(1, 18)), finished_at(day(2, 23))];
+        history.push(WorkoutRecord::new(
+            &routine(),
+            &settings(),
+            day(3, 9),
+            day(3, 9),
+            Duration::from_secs(45),
+        ));
+
+        let days = daily_work_secs(&history, utc);
+        let date = |d| NaiveDate::from_ymd_opt(2023, 5, d).unwrap();
+        assert_eq!(days.get(&date(1)), Some(&120));
+        assert_eq!(days.get(&date(2)), Some(&60));
+        assert_eq!(days.get(&date(3)), None);
+
+        // An hour east, the late workout moves to the next day
+        let days = daily_work_secs(&history, FixedOffset::east_opt(3600).unwrap());
+        assert_eq!(days.get(&date(2)), None);
+        assert_eq!(days.get(&date(3)), Some(&60));
+    }
+
+    #[test]
+    fn test_heat_level() {
+        assert_eq!(heat_level(0), 0);
+        assert_eq!(heat_level(30), 1);
+        assert_eq!(heat_level(5 * 60), 2);
+        assert_eq!(heat_level(15 * 60), 3);
+        assert_eq!(heat_level(45 * 60), 4);
+    }
+
+    #[test]
+    fn test_history_stats_empty() {
+        let now = Utc.with_ymd_and_hms(2023, 5, 1, 10, 0, 0).unwrap().fixed_offset();
+        let stats = HistoryStats::new(&[], now);
+        assert_eq!(stats, HistoryStats::default());
+    }
+}
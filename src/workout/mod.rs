@@ -0,0 +1,5 @@
+pub mod clock;
+pub mod cues;
+pub mod history;
+pub mod schedule;
+pub mod session;
@@ -0,0 +1,958 @@
+use itertools::Itertools;
+use std::time::Duration;
+
+use crate::components::exercises::{find_exercise, EXERCISES};
+use crate::components::routine_card::{Routine, RoutineExercise};
+use crate::components::settings::WorkoutSettings;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stage {
+    pub duration: Duration,
+    pub is_high_intensity: bool,
+    pub label: String,
+    /// Reps to finish within the stage, resting for whatever time is left.
+    pub reps: Option<u32>,
+    /// Starts a minute of an EMOM workout, which is announced with "Go".
+    pub emom: bool,
+    /// As many rounds as possible: the user taps to log each completed round.
+    pub amrap: bool,
+    /// Which half of a one-sided exercise this stage is.
+    pub side: Option<Side>,
+    /// Set for the low-intensity stages around the main workout.
+    pub phase: Option<Phase>,
+}
+
+/// Low-intensity mobility work before or after the main workout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    WarmUp,
+    CoolDown,
+}
+
+impl Phase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Phase::WarmUp => "Warm-up",
+            Phase::CoolDown => "Cool-down",
+        }
+    }
+}
+
+/// Half of the work time of a unilateral exercise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Side::Left => "Left side",
+            Side::Right => "Right side",
+        }
+    }
+}
+
+/// The full list of stages for a routine under a given set of workout settings.
+///
+/// This is a plain value with no reactive dependencies, so it can be computed in
+/// tests, on the server, or anywhere outside a Leptos owner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    stages: Vec<Stage>,
+}
+
+/// Length of the "Prepare" stage in new settings.
+pub const DEFAULT_PREPARE_DURATION_SECS: u32 = 10;
+
+// The Tabata protocol: eight rounds of 20s work and 10s rest for each exercise
+pub const TABATA_WORK_SECS: u32 = 20;
+pub const TABATA_REST_SECS: u32 = 10;
+pub const TABATA_ROUNDS: u32 = 8;
+pub const TABATA_BLOCK_BREAK_SECS: u32 = 60;
+
+/// Time spent on each mobility exercise during warm-up and cool-down.
+pub const PHASE_EXERCISE_SECS: u32 = 30;
+
+/// Exercises the warm-up and cool-down are made of.
+const PHASE_EXERCISE_GROUP: &str = "Mobility";
+
+/// Each EMOM stage starts on the minute.
+pub const EMOM_INTERVAL_SECS: u32 = 60;
+
+/// How a routine's exercises are laid out into stages.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkoutMode {
+    /// Every exercise once per set, using the work and rest settings.
+    #[default]
+    Standard,
+    /// A fixed block of Tabata rounds per exercise, ignoring the timing settings.
+    Tabata,
+    /// Every minute on the minute: one exercise per minute, cycling through the
+    /// exercises once per set.
+    Emom,
+}
+
+impl WorkoutMode {
+    pub const ALL: [WorkoutMode; 3] = [WorkoutMode::Standard, WorkoutMode::Tabata, WorkoutMode::Emom];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WorkoutMode::Standard => "Standard",
+            WorkoutMode::Tabata => "Tabata",
+            WorkoutMode::Emom => "EMOM",
+        }
+    }
+
+    /// Stable identifier for use in form values.
+    pub fn key(&self) -> &'static str {
+        match self {
+            WorkoutMode::Standard => "standard",
+            WorkoutMode::Tabata => "tabata",
+            WorkoutMode::Emom => "emom",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<WorkoutMode> {
+        Self::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+}
+
+/// Timing values in effect for a routine once its overrides are applied.
+///
+/// In Tabata mode `sets` is the number of rounds per exercise and
+/// `rest_set_duration_secs` the break between exercise blocks. In EMOM mode
+/// every exercise gets a full interval of work and there are no rests. A
+/// non-empty `work_ladder` gives the work duration of each set in turn. The
+/// warm-up and cool-down come from the settings whatever the mode.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WorkoutTiming {
+    pub high_intensity_duration_secs: u32,
+    pub rest_exercise_duration_secs: u32,
+    pub rest_set_duration_secs: u32,
+    pub prepare_duration_secs: u32,
+    pub sets: u32,
+    #[serde(default)]
+    pub mode: WorkoutMode,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub work_ladder: Vec<u32>,
+    #[serde(default)]
+    pub warm_up_secs: u32,
+    #[serde(default)]
+    pub cool_down_secs: u32,
+}
+
+impl WorkoutTiming {
+    /// Routine overrides take precedence over the global settings.
+    pub fn resolve(routine: &Routine, settings: &WorkoutSettings) -> Self {
+        let overrides = routine.effective_overrides(settings);
+        let prepare_duration_secs = overrides
+            .prepare_duration_secs
+            .unwrap_or(settings.prepare_duration_secs);
+        match overrides.mode {
+            Some(WorkoutMode::Tabata) => {
+                return WorkoutTiming {
+                    high_intensity_duration_secs: TABATA_WORK_SECS,
+                    rest_exercise_duration_secs: TABATA_REST_SECS,
+                    rest_set_duration_secs: TABATA_BLOCK_BREAK_SECS,
+                    prepare_duration_secs,
+                    sets: TABATA_ROUNDS,
+                    mode: WorkoutMode::Tabata,
+                    work_ladder: Vec::new(),
+                    warm_up_secs: settings.warm_up_secs,
+                    cool_down_secs: settings.cool_down_secs,
+                }
+            }
+            Some(WorkoutMode::Emom) => {
+                return WorkoutTiming {
+                    high_intensity_duration_secs: EMOM_INTERVAL_SECS,
+                    rest_exercise_duration_secs: 0,
+                    rest_set_duration_secs: 0,
+                    prepare_duration_secs,
+                    sets: overrides.sets.unwrap_or(settings.sets),
+                    mode: WorkoutMode::Emom,
+                    work_ladder: Vec::new(),
+                    warm_up_secs: settings.warm_up_secs,
+                    cool_down_secs: settings.cool_down_secs,
+                }
+            }
+            Some(WorkoutMode::Standard) | None => {}
+        }
+        WorkoutTiming {
+            high_intensity_duration_secs: overrides
+                .high_intensity_duration_secs
+                .unwrap_or(settings.high_intensity_duration_secs),
+            rest_exercise_duration_secs: overrides
+                .rest_exercise_duration_secs
+                .unwrap_or(settings.rest_exercise_duration_secs),
+            rest_set_duration_secs: overrides
+                .rest_set_duration_secs
+                .unwrap_or(settings.rest_set_duration_secs),
+            prepare_duration_secs,
+            sets: match overrides.work_ladder.len() {
+                0 => overrides.sets.unwrap_or(settings.sets),
+                steps => steps as u32,
+            },
+            mode: WorkoutMode::Standard,
+            work_ladder: overrides.work_ladder,
+            warm_up_secs: settings.warm_up_secs,
+            cool_down_secs: settings.cool_down_secs,
+        }
+    }
+
+    /// Work duration for the set at `index`, counting from zero.
+    pub fn work_secs_for_set(&self, index: usize) -> u32 {
+        self.work_ladder
+            .get(index)
+            .copied()
+            .unwrap_or(self.high_intensity_duration_secs)
+    }
+}
+
+impl Schedule {
+    pub fn new(routine: &Routine, settings: &WorkoutSettings) -> Self {
+        let timing = WorkoutTiming::resolve(routine, settings);
+
+        // Prepare stage
+        let prepare_stage = rest_stage("Prepare", timing.prepare_duration_secs);
+
+        let stages = match timing.mode {
+            WorkoutMode::Standard => standard_stages(routine, &timing),
+            WorkoutMode::Tabata => tabata_stages(routine, &timing),
+            WorkoutMode::Emom => emom_stages(routine, &timing),
+        };
+
+        // Combine prepare stage and warm-up with the rest of the stages
+        Self {
+            stages: std::iter::once(prepare_stage)
+                .chain(phase_stages(Phase::WarmUp, timing.warm_up_secs))
+                .chain(stages)
+                .chain(phase_stages(Phase::CoolDown, timing.cool_down_secs))
+                .collect(),
+        }
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.stages.iter().map(|stage| stage.duration).sum()
+    }
+
+    /// Index of the first high-intensity stage, after the prepare stage and any warm-up.
+    pub fn first_exercise_index(&self) -> Option<usize> {
+        self.stages.iter().position(|stage| stage.is_high_intensity)
+    }
+
+    /// Number of stages up to the end of the main workout, leaving out the
+    /// cool-down, which is optional to finish.
+    pub fn main_stage_count(&self) -> usize {
+        self.stages
+            .iter()
+            .rposition(|stage| stage.phase.is_none())
+            .map_or(0, |index| index + 1)
+    }
+
+    /// Time at which the stage at `index` begins.
+    pub fn stage_start(&self, index: usize) -> Duration {
+        self.stages.iter().take(index).map(|stage| stage.duration).sum()
+    }
+
+    /// Start of the first non-empty stage after the one running at `t`, if any.
+    pub fn next_stage_start(&self, t: Duration) -> Option<Duration> {
+        let mut start = Duration::ZERO;
+        for stage in &self.stages {
+            if start > t && !stage.duration.is_zero() {
+                return Some(start);
+            }
+            start += stage.duration;
+        }
+        None
+    }
+
+    /// Start of the last non-empty stage before the one running at `t`. Once the
+    /// workout is over, this is the start of the final stage.
+    pub fn previous_stage_start(&self, t: Duration) -> Duration {
+        let mut start = Duration::ZERO;
+        let mut previous = Duration::ZERO;
+        for stage in &self.stages {
+            let end = start + stage.duration;
+            if end > t {
+                break;
+            }
+            if !stage.duration.is_zero() {
+                previous = start;
+            }
+            start = end;
+        }
+        previous
+    }
+
+    /// Number of stages fully behind `t`.
+    pub fn stages_completed(&self, t: Duration) -> usize {
+        self.stage_at_t(t.as_secs_f64())
+            .map_or(self.stages.len(), |(index, ..)| index)
+    }
+
+    /// High-intensity time covered by the first `t` of the workout.
+    pub fn work_time_until(&self, t: Duration) -> Duration {
+        let mut start = Duration::ZERO;
+        let mut work = Duration::ZERO;
+        for stage in &self.stages {
+            if stage.is_high_intensity && t > start {
+                work += stage.duration.min(t - start);
+            }
+            start += stage.duration;
+        }
+        work
+    }
+
+    pub fn stage_at_t(&self, t: f64) -> Option<(usize, Stage, Option<Stage>, f64)> {
+        let stages = &self.stages;
+        let mut cumulative_duration = 0.0;
+
+        for i in 0..stages.len() {
+            let current_stage = &stages[i];
+            let stage_start = cumulative_duration;
+            cumulative_duration += current_stage.duration.as_secs_f64();
+
+            if t < cumulative_duration {
+                let next_stage = stages.get(i + 1).cloned();
+                let time_in_stage = t - stage_start;
+                return Some((i, current_stage.clone(), next_stage, time_in_stage));
+            }
+        }
+
+        None
+    }
+}
+
+fn work_stage(label: &str, secs: u32) -> Stage {
+    Stage {
+        duration: Duration::from_secs(secs as u64),
+        is_high_intensity: true,
+        label: label.to_string(),
+        reps: None,
+        emom: false,
+        amrap: false,
+        side: None,
+        phase: None,
+    }
+}
+
+fn rest_stage(label: &str, secs: u32) -> Stage {
+    Stage {
+        duration: Duration::from_secs(secs as u64),
+        is_high_intensity: false,
+        label: label.to_string(),
+        reps: None,
+        emom: false,
+        amrap: false,
+        side: None,
+        phase: None,
+    }
+}
+
+/// Work stages for one exercise, split into left and right halves if it is
+/// worked one side at a time.
+fn exercise_stages(exercise: &RoutineExercise, secs: u32) -> Vec<Stage> {
+    let stage = Stage {
+        amrap: exercise.amrap,
+        ..work_stage(&exercise.name, secs)
+    };
+    if !find_exercise(&exercise.name).is_some_and(|e| e.unilateral) {
+        return vec![stage];
+    }
+    let left_secs = secs / 2;
+    vec![
+        Stage {
+            duration: Duration::from_secs(left_secs as u64),
+            side: Some(Side::Left),
+            ..stage.clone()
+        },
+        Stage {
+            duration: Duration::from_secs((secs - left_secs) as u64),
+            side: Some(Side::Right),
+            ..stage
+        },
+    ]
+}
+
+/// Mobility exercises filling `secs`, in order for the warm-up and in reverse
+/// for the cool-down. The last exercise is cut short if time runs out.
+fn phase_stages(phase: Phase, secs: u32) -> Vec<Stage> {
+    let mut exercises: Vec<RoutineExercise> = EXERCISES
+        .iter()
+        .filter(|exercise| exercise.group == PHASE_EXERCISE_GROUP)
+        .map(|exercise| exercise.name.into())
+        .collect();
+    if phase == Phase::CoolDown {
+        exercises.reverse();
+    }
+    let mut remaining = secs;
+    exercises
+        .iter()
+        .cycle()
+        .map_while(|exercise| {
+            let secs = remaining.min(PHASE_EXERCISE_SECS);
+            remaining -= secs;
+            (secs > 0).then(|| exercise_stages(exercise, secs))
+        })
+        .flatten()
+        .map(|stage| Stage {
+            is_high_intensity: false,
+            phase: Some(phase),
+            ..stage
+        })
+        .collect()
+}
+
+/// All exercises once per set, with set breaks in between.
+#[allow(unstable_name_collisions)]
+fn standard_stages(routine: &Routine, timing: &WorkoutTiming) -> Vec<Stage> {
+    // Create a set of exercise stages, each followed by a rest except the last.
+    // Exercises may carry their own work and rest times.
+    let exercise_count = routine.exercises.len();
+    let single_set = |set: usize| -> Vec<Stage> {
+        routine
+            .exercises
+            .iter()
+            .enumerate()
+            .flat_map(|(index, exercise)| {
+                let exercise_stages = exercise_stages(
+                    exercise,
+                    exercise.duration_secs.unwrap_or(timing.work_secs_for_set(set)),
+                );
+                let rest_stage = (index + 1 < exercise_count).then(|| {
+                    rest_stage(
+                        "Rest",
+                        exercise.rest_after_secs.unwrap_or(timing.rest_exercise_duration_secs),
+                    )
+                });
+                exercise_stages.into_iter().chain(rest_stage)
+            })
+            .collect()
+    };
+
+    // Create the sets and intersperse set breaks
+    (0..timing.sets as usize)
+        .map(single_set)
+        .intersperse(vec![rest_stage("Set Break", timing.rest_set_duration_secs)])
+        .flatten()
+        .collect()
+}
+
+/// One interval per exercise, labelled with its rep target, for each set. A
+/// one-sided exercise gives each side half of its minute.
+fn emom_stages(routine: &Routine, timing: &WorkoutTiming) -> Vec<Stage> {
+    let single_set = routine.exercises.iter().flat_map(|exercise| {
+        let label = match exercise.reps {
+            Some(reps) => format!("{} {}", reps, exercise.name),
+            None => exercise.name.clone(),
+        };
+        exercise_stages(exercise, timing.high_intensity_duration_secs)
+            .into_iter()
+            .map(move |stage| Stage {
+                label: label.clone(),
+                reps: exercise.reps,
+                // The minute starts with the first side
+                emom: stage.side != Some(Side::Right),
+                ..stage
+            })
+    });
+    std::iter::repeat(single_set)
+        .take(timing.sets as usize)
+        .flatten()
+        .collect()
+}
+
+/// A block of rounds per exercise, each round a work stage and a rest. The
+/// block break replaces the last rest of a block, and the last block ends on
+/// its final work stage.
+#[allow(unstable_name_collisions)]
+fn tabata_stages(routine: &Routine, timing: &WorkoutTiming) -> Vec<Stage> {
+    routine
+        .exercises
+        .iter()
+        .map(|exercise| {
+            std::iter::repeat(exercise_stages(exercise, timing.high_intensity_duration_secs))
+                .take(timing.sets as usize)
+                .intersperse(vec![rest_stage("Rest", timing.rest_exercise_duration_secs)])
+                .flatten()
+                .collect::<Vec<_>>()
+        })
+        .intersperse(vec![rest_stage("Block Break", timing.rest_set_duration_secs)])
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::components::routine_card::{RoutineExercise, TimingOverrides};
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+
+    pub(crate) fn routine(exercises: &[&str]) -> Routine {
+        Routine {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            exercises: exercises.iter().map(|&e| e.into()).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn settings(high: u32, rest: u32, set_rest: u32, sets: u32) -> WorkoutSettings {
+        WorkoutSettings {
+            high_intensity_duration_secs: high,
+            rest_exercise_duration_secs: rest,
+            rest_set_duration_secs: set_rest,
+            sets,
+            ..WorkoutSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_stage_layout() {
+        let schedule = Schedule::new(&routine(&["Squats", "Burpees"]), &settings(30, 15, 45, 2));
+        let labels: Vec<&str> = schedule.stages().iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "Prepare",
+                "Squats",
+                "Rest",
+                "Burpees",
+                "Set Break",
+                "Squats",
+                "Rest",
+                "Burpees"
+            ]
+        );
+        assert_eq!(schedule.duration(), Duration::from_secs(10 + 4 * 30 + 2 * 15 + 45));
+    }
+
+    #[test]
+    fn test_stage_at_t() {
+        let schedule = Schedule::new(&routine(&["Squats", "Burpees"]), &settings(30, 15, 45, 1));
+
+        let (index, current, next, time_in_stage) = schedule.stage_at_t(0.0).unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(current.label, "Prepare");
+        assert_eq!(next.unwrap().label, "Squats");
+        assert_eq!(time_in_stage, 0.0);
+
+        let (index, current, _, time_in_stage) = schedule.stage_at_t(12.5).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(current.label, "Squats");
+        assert_eq!(time_in_stage, 2.5);
+
+        let (index, _, next, _) = schedule.stage_at_t(schedule.duration().as_secs_f64() - 0.1).unwrap();
+        assert_eq!(index, 3);
+        assert!(next.is_none());
+
+        assert!(schedule.stage_at_t(schedule.duration().as_secs_f64()).is_none());
+    }
+
+    #[test]
+    fn test_stage_boundaries() {
+        // Stages: Prepare 0-10, Squats 10-40, Rest 40-55, Burpees 55-85
+        let schedule = Schedule::new(&routine(&["Squats", "Burpees"]), &settings(30, 15, 45, 1));
+        let secs = Duration::from_secs;
+
+        assert_eq!(schedule.stage_start(0), secs(0));
+        assert_eq!(schedule.stage_start(2), secs(40));
+        assert_eq!(schedule.stage_start(4), schedule.duration());
+
+        assert_eq!(schedule.next_stage_start(secs(0)), Some(secs(10)));
+        assert_eq!(schedule.next_stage_start(secs(10)), Some(secs(40)));
+        assert_eq!(schedule.next_stage_start(secs(50)), Some(secs(55)));
+        assert_eq!(schedule.next_stage_start(secs(60)), None);
+
+        assert_eq!(schedule.previous_stage_start(secs(5)), secs(0));
+        assert_eq!(schedule.previous_stage_start(secs(10)), secs(0));
+        assert_eq!(schedule.previous_stage_start(secs(45)), secs(10));
+        assert_eq!(schedule.previous_stage_start(secs(85)), secs(55));
+    }
+
+    #[test]
+    fn test_stage_boundaries_skip_empty_stages() {
+        // Without rest the Rest stages are zero-length and should never be landed on
+        let schedule = Schedule::new(&routine(&["Squats", "Burpees"]), &settings(30, 0, 45, 1));
+        let secs = Duration::from_secs;
+
+        assert_eq!(schedule.next_stage_start(secs(10)), Some(secs(40)));
+        assert_eq!(schedule.stage_at_t(40.0).unwrap().1.label, "Burpees");
+        assert_eq!(schedule.previous_stage_start(secs(45)), secs(10));
+    }
+
+    #[test]
+    fn test_progress_until() {
+        // Stages: Prepare 0-10, Squats 10-40, Rest 40-55, Burpees 55-85
+        let schedule = Schedule::new(&routine(&["Squats", "Burpees"]), &settings(30, 15, 45, 1));
+        let secs = Duration::from_secs;
+
+        assert_eq!(schedule.stages_completed(secs(0)), 0);
+        assert_eq!(schedule.stages_completed(secs(45)), 2);
+        assert_eq!(schedule.stages_completed(secs(85)), 4);
+
+        assert_eq!(schedule.work_time_until(secs(5)), secs(0));
+        assert_eq!(schedule.work_time_until(secs(25)), secs(15));
+        assert_eq!(schedule.work_time_until(secs(60)), secs(35));
+        assert_eq!(schedule.work_time_until(secs(200)), secs(60));
+    }
+
+    #[test]
+    fn test_routine_overrides() {
+        let mut routine = routine(&["Low plank", "Crunches"]);
+        routine.overrides = TimingOverrides {
+            high_intensity_duration_secs: Some(60),
+            prepare_duration_secs: Some(20),
+            sets: Some(1),
+            ..Default::default()
+        };
+        let settings = settings(30, 15, 45, 3);
+
+        let schedule = Schedule::new(&routine, &settings);
+        let durations: Vec<u64> = schedule.stages().iter().map(|s| s.duration.as_secs()).collect();
+        assert_eq!(durations, [20, 60, 15, 60]);
+    }
+
+    #[test]
+    fn test_settings_overrides_take_precedence() {
+        let mut routine = routine(&["Low plank"]);
+        routine.overrides = TimingOverrides {
+            high_intensity_duration_secs: Some(60),
+            sets: Some(1),
+            ..Default::default()
+        };
+        let mut settings = settings(30, 15, 45, 3);
+        settings.routine_overrides.insert(
+            routine.id.clone(),
+            TimingOverrides {
+                high_intensity_duration_secs: Some(90),
+                ..Default::default()
+            },
+        );
+
+        // The settings override wins, the routine's own set count still applies
+        let schedule = Schedule::new(&routine, &settings);
+        let durations: Vec<u64> = schedule.stages().iter().map(|s| s.duration.as_secs()).collect();
+        assert_eq!(durations, [10, 90]);
+
+        // Other routines are unaffected
+        let other = Routine {
+            id: "other".to_string(),
+            ..routine.clone()
+        };
+        assert_eq!(Schedule::new(&other, &settings).stages()[1].duration.as_secs(), 60);
+    }
+
+    #[test]
+    fn test_exercise_durations() {
+        let mut routine = routine(&["Low plank", "Burpees", "Crunches"]);
+        routine.exercises[0] = RoutineExercise {
+            name: "Low plank".to_string(),
+            duration_secs: Some(60),
+            rest_after_secs: Some(30),
+            reps: None,
+            amrap: false,
+        };
+        // Rest after the last exercise of a set is replaced by the set break
+        routine.exercises[2].rest_after_secs = Some(5);
+
+        let schedule = Schedule::new(&routine, &settings(20, 10, 45, 2));
+        let durations: Vec<u64> = schedule.stages().iter().map(|s| s.duration.as_secs()).collect();
+        assert_eq!(durations, [10, 60, 30, 20, 10, 20, 45, 60, 30, 20, 10, 20]);
+    }
+
+    #[test]
+    fn test_unilateral_exercises_switch_sides() {
+        let schedule = Schedule::new(&routine(&["Squats", "Lunges"]), &settings(45, 15, 30, 1));
+        let stages: Vec<_> = schedule
+            .stages()
+            .iter()
+            .map(|s| (s.label.as_str(), s.duration.as_secs(), s.side))
+            .collect();
+        // An odd work time gives the extra second to the second side
+        assert_eq!(
+            stages,
+            [
+                ("Prepare", 10, None),
+                ("Squats", 45, None),
+                ("Rest", 15, None),
+                ("Lunges", 22, Some(Side::Left)),
+                ("Lunges", 23, Some(Side::Right)),
+            ]
+        );
+        assert_eq!(schedule.work_time_until(schedule.duration()).as_secs(), 90);
+
+        // Tabata rounds and EMOM minutes are split the same way
+        for mode in [WorkoutMode::Tabata, WorkoutMode::Emom] {
+            let mut routine = routine(&["Lunges"]);
+            routine.overrides.mode = Some(mode.clone());
+            let schedule = Schedule::new(&routine, &settings(45, 15, 30, 1));
+            let work: Vec<_> = schedule.stages().iter().filter(|s| s.is_high_intensity).collect();
+            let (secs, rounds) = match mode {
+                WorkoutMode::Tabata => (TABATA_WORK_SECS, TABATA_ROUNDS),
+                _ => (EMOM_INTERVAL_SECS, 1),
+            };
+            assert_eq!(work.len(), 2 * rounds as usize, "{:?}", mode);
+            for pair in work.chunks(2) {
+                assert_eq!(pair[0].side, Some(Side::Left), "{:?}", mode);
+                assert_eq!(pair[1].side, Some(Side::Right), "{:?}", mode);
+                assert_eq!(pair[0].duration + pair[1].duration, Duration::from_secs(secs as u64));
+            }
+        }
+    }
+
+    #[test]
+    fn test_warm_up_and_cool_down() {
+        let with_phases = WorkoutSettings {
+            warm_up_secs: 75,
+            cool_down_secs: 30,
+            ..settings(30, 15, 45, 1)
+        };
+        let schedule = Schedule::new(&routine(&["Squats"]), &with_phases);
+        let stages: Vec<_> = schedule
+            .stages()
+            .iter()
+            .map(|s| (s.label.as_str(), s.duration.as_secs(), s.phase))
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                ("Prepare", 10, None),
+                ("Squat to Toe Touch", 30, Some(Phase::WarmUp)),
+                ("Downward to Upward Dog", 30, Some(Phase::WarmUp)),
+                ("Lunge and Reach", 7, Some(Phase::WarmUp)),
+                ("Lunge and Reach", 8, Some(Phase::WarmUp)),
+                ("Squats", 30, None),
+                ("90/90 Hip Stretch", 15, Some(Phase::CoolDown)),
+                ("90/90 Hip Stretch", 15, Some(Phase::CoolDown)),
+            ]
+        );
+        // Warm-up and cool-down aren't counted as work
+        assert_eq!(schedule.work_time_until(schedule.duration()).as_secs(), 30);
+        assert_eq!(schedule.first_exercise_index(), Some(5));
+        assert!(Schedule::new(&routine(&["Squats"]), &settings(30, 15, 45, 1))
+            .stages()
+            .iter()
+            .all(|s| s.phase.is_none()));
+    }
+
+    #[test]
+    fn test_work_ladder() {
+        let mut routine = routine(&["Burpees", "Squats"]);
+        routine.overrides.work_ladder = vec![20, 30, 40, 30, 20];
+        // Exercise times still win over the ladder
+        routine.exercises[1].duration_secs = Some(15);
+
+        let schedule = Schedule::new(&routine, &settings(45, 10, 30, 2));
+        let work: Vec<u64> = schedule
+            .stages()
+            .iter()
+            .filter(|s| s.is_high_intensity)
+            .map(|s| s.duration.as_secs())
+            .collect();
+        // The ladder decides the number of sets
+        assert_eq!(work, [20, 15, 30, 15, 40, 15, 30, 15, 20, 15]);
+        assert_eq!(
+            schedule.duration().as_secs(),
+            10 + (20 + 30 + 40 + 30 + 20) + 5 * (10 + 15) + 4 * 30
+        );
+        assert_eq!(WorkoutTiming::resolve(&routine, &settings(45, 10, 30, 2)).sets, 5);
+    }
+
+    #[test]
+    fn test_amrap_stages() {
+        let mut routine = routine(&["Burpees", "Squats"]);
+        routine.exercises[1].amrap = true;
+
+        let schedule = Schedule::new(&routine, &settings(20, 10, 45, 2));
+        let amrap: Vec<usize> = (0..schedule.stages().len())
+            .filter(|&i| schedule.stages()[i].amrap)
+            .collect();
+        // Only the marked exercise, once per set
+        assert_eq!(amrap, [3, 7]);
+        assert!(amrap.iter().all(|&i| schedule.stages()[i].label == "Squats"));
+
+        // Every Tabata round and every EMOM minute of the exercise counts rounds too
+        for (mode, expected) in [(WorkoutMode::Tabata, 8), (WorkoutMode::Emom, 2)] {
+            let mut routine = routine.clone();
+            routine.overrides.mode = Some(mode.clone());
+            let schedule = Schedule::new(&routine, &settings(20, 10, 45, 2));
+            let amrap: Vec<&Stage> = schedule.stages().iter().filter(|s| s.amrap).collect();
+            assert_eq!(amrap.len(), expected, "{:?}", mode);
+            assert!(amrap.iter().all(|s| s.label == "Squats"), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_tabata_mode() {
+        let mut routine = routine(&["Burpees", "Squats"]);
+        routine.overrides.mode = Some(WorkoutMode::Tabata);
+        // Global settings and per-exercise times don't apply to Tabata
+        routine.exercises[0].duration_secs = Some(45);
+
+        let schedule = Schedule::new(&routine, &settings(30, 15, 45, 3));
+        let stages = schedule.stages();
+
+        // Prepare, then 8 rounds of work and rest per block, where the block break
+        // takes the place of the first block's 8th rest and the workout ends on work
+        assert_eq!(stages.len(), 1 + 16 + 16 - 1);
+        for block in [&stages[1..17], &stages[17..]] {
+            for (round, pair) in block.chunks(2).enumerate() {
+                assert!(pair[0].is_high_intensity, "round {}", round + 1);
+                assert_eq!(pair[0].duration.as_secs(), 20);
+                if let Some(rest) = pair.get(1) {
+                    assert!(!rest.is_high_intensity);
+                    let expected = if round == 7 { ("Block Break", 60) } else { ("Rest", 10) };
+                    assert_eq!((rest.label.as_str(), rest.duration.as_secs()), expected);
+                }
+            }
+        }
+        assert_eq!(stages.last().unwrap().label, "Squats");
+        let work: Vec<_> = stages.iter().filter(|s| s.is_high_intensity).collect();
+        assert_eq!(work.len(), 16);
+        assert!(work.iter().all(|s| s.duration.as_secs() == 20));
+        assert!(work[..8].iter().all(|s| s.label == "Burpees"));
+        assert!(work[8..].iter().all(|s| s.label == "Squats"));
+        assert_eq!(schedule.duration().as_secs(), 10 + 2 * (8 * 20 + 7 * 10) + 60);
+    }
+
+    #[test]
+    fn test_tabata_from_settings_override() {
+        let routine = routine(&["Burpees"]);
+        let mut settings = settings(30, 15, 45, 3);
+        settings.routine_overrides.insert(
+            routine.id.clone(),
+            TimingOverrides {
+                mode: Some(WorkoutMode::Tabata),
+                prepare_duration_secs: Some(5),
+                ..Default::default()
+            },
+        );
+
+        let timing = WorkoutTiming::resolve(&routine, &settings);
+        assert_eq!(timing.mode, WorkoutMode::Tabata);
+        assert_eq!(timing.sets, TABATA_ROUNDS);
+        assert_eq!(timing.prepare_duration_secs, 5);
+        assert_eq!(
+            Schedule::new(&routine, &settings).duration().as_secs(),
+            5 + 8 * 20 + 7 * 10
+        );
+    }
+
+    #[test]
+    fn test_emom_mode() {
+        let mut routine = routine(&["Burpees", "Squats"]);
+        routine.overrides = TimingOverrides {
+            mode: Some(WorkoutMode::Emom),
+            sets: Some(3),
+            ..Default::default()
+        };
+        routine.exercises[0].reps = Some(12);
+
+        let schedule = Schedule::new(&routine, &settings(30, 15, 45, 5));
+        let stages = &schedule.stages()[1..];
+
+        // Back to back minutes, no rest stages
+        assert_eq!(stages.len(), 6);
+        assert!(stages.iter().all(|s| s.is_high_intensity && s.duration.as_secs() == 60));
+        assert_eq!(stages[0].label, "12 Burpees");
+        assert_eq!(stages[0].reps, Some(12));
+        assert_eq!(stages[1].label, "Squats");
+        assert_eq!(stages[1].reps, None);
+        assert_eq!(stages[2].label, "12 Burpees");
+        assert_eq!(schedule.duration().as_secs(), 10 + 6 * 60);
+    }
+
+    // Settings and routines small enough to expand quickly
+    #[derive(Clone, Debug)]
+    struct SmallSchedule {
+        exercises: Vec<String>,
+        settings: WorkoutSettings,
+    }
+
+    impl Arbitrary for SmallSchedule {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let names = ["Squats", "Lunges", "Burpees", "Crunches", "Superman"];
+            let count = usize::arbitrary(g) % 6;
+            let exercises = (0..count)
+                .map(|_| names[usize::arbitrary(g) % names.len()].to_string())
+                .collect();
+            let settings = settings(
+                u32::arbitrary(g) % 301,
+                u32::arbitrary(g) % 121,
+                u32::arbitrary(g) % 121,
+                u32::arbitrary(g) % 31,
+            );
+            SmallSchedule { exercises, settings }
+        }
+    }
+
+    impl SmallSchedule {
+        fn schedule(&self) -> Schedule {
+            let routine = Routine {
+                exercises: self.exercises.iter().map(|e| e.as_str().into()).collect(),
+                ..routine(&[])
+            };
+            Schedule::new(&routine, &self.settings)
+        }
+    }
+
+    #[quickcheck]
+    fn prop_exercises_follow_routine_order(input: SmallSchedule) -> bool {
+        let schedule = input.schedule();
+        let work: Vec<String> = schedule
+            .stages()
+            .iter()
+            // One-sided exercises count once, not once per side
+            .filter(|s| s.is_high_intensity && s.side != Some(Side::Right))
+            .map(|s| s.label.clone())
+            .collect();
+        let expected: Vec<String> = std::iter::repeat(input.exercises.clone())
+            .take(input.settings.sets as usize)
+            .flatten()
+            .collect();
+        schedule.stages()[0].label == "Prepare" && work == expected
+    }
+
+    #[quickcheck]
+    fn prop_duration_matches_settings(input: SmallSchedule) -> bool {
+        let s = &input.settings;
+        let n = input.exercises.len() as u64;
+        let sets = s.sets as u64;
+        let expected = 10
+            + sets * n * s.high_intensity_duration_secs as u64
+            + sets * n.saturating_sub(1) * s.rest_exercise_duration_secs as u64
+            + sets.saturating_sub(1) * s.rest_set_duration_secs as u64;
+        input.schedule().duration() == Duration::from_secs(expected)
+    }
+
+    #[quickcheck]
+    fn prop_stage_at_t_is_consistent(input: SmallSchedule, t: u32) -> bool {
+        let schedule = input.schedule();
+        let total = schedule.duration().as_secs_f64();
+        let t = t as f64 % (total + 10.0);
+
+        match schedule.stage_at_t(t) {
+            Some((index, current, next, time_in_stage)) => {
+                let start: f64 = schedule.stages()[..index]
+                    .iter()
+                    .map(|s| s.duration.as_secs_f64())
+                    .sum();
+                t < total
+                    && current == schedule.stages()[index]
+                    && next.as_ref() == schedule.stages().get(index + 1)
+                    && time_in_stage == t - start
+                    && time_in_stage < current.duration.as_secs_f64()
+            }
+            None => t >= total,
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+use crate::components::routine_card::Routine;
+use crate::components::settings::WorkoutSettings;
+use crate::data::routines::find_routine;
+use crate::workout::history::{StageRounds, WorkoutRecord};
+
+const SESSION_KEY: &str = "hiit_session";
+
+/// A session found in storage.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SavedSession {
+    Resumable(WorkoutSession),
+    /// Saved longer ago than the resume window. It has been removed from storage
+    /// and should be logged to history instead.
+    Expired(WorkoutSession),
+}
+
+impl SavedSession {
+    pub fn new(session: WorkoutSession, now: DateTime<Utc>, resume_window_mins: u32) -> Self {
+        match session.is_expired(now, resume_window_mins) {
+            true => SavedSession::Expired(session),
+            false => SavedSession::Resumable(session),
+        }
+    }
+
+    pub fn into_session(self) -> WorkoutSession {
+        match self {
+            SavedSession::Resumable(session) | SavedSession::Expired(session) => session,
+        }
+    }
+}
+
+/// An in-progress workout, persisted so it can be resumed after a reload or
+/// after navigating away from the timer.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WorkoutSession {
+    pub routine_id: String,
+    /// Settings the workout was started with, so the resumed schedule matches.
+    pub settings: WorkoutSettings,
+    pub elapsed_secs: f64,
+    pub paused: bool,
+    pub saved_at: DateTime<Utc>,
+    /// Missing from sessions saved before workouts were logged to history.
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
+    /// Rounds tapped so far during AMRAP stages.
+    #[serde(default)]
+    pub rounds: Vec<StageRounds>,
+}
+
+impl WorkoutSession {
+    pub fn new(
+        routine_id: String,
+        settings: &WorkoutSettings,
+        elapsed: Duration,
+        paused: bool,
+        started_at: DateTime<Utc>,
+        saved_at: DateTime<Utc>,
+        rounds: Vec<StageRounds>,
+    ) -> Self {
+        // Only the timing settings matter for a session; don't copy logs or routines
+        let settings = WorkoutSettings {
+            history: Vec::new(),
+            custom_routines: Vec::new(),
+            ..settings.clone()
+        };
+        Self {
+            routine_id,
+            settings,
+            elapsed_secs: elapsed.as_secs_f64(),
+            paused,
+            saved_at,
+            started_at: Some(started_at),
+            rounds,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64(self.elapsed_secs.max(0.0))
+    }
+
+    /// When the workout was first started, estimated for older sessions.
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+            .unwrap_or_else(|| self.saved_at - chrono::Duration::from_std(self.elapsed()).unwrap_or_default())
+    }
+
+    /// Whether the session was saved longer than `resume_window_mins` before `now`.
+    pub fn is_expired(&self, now: DateTime<Utc>, resume_window_mins: u32) -> bool {
+        now - self.saved_at > chrono::Duration::minutes(resume_window_mins as i64)
+    }
+
+    /// History record of the session as far as it got, run with its saved settings.
+    /// Custom routines aren't part of the snapshot, so they're looked up in `settings`.
+    pub fn record(&self, settings: &WorkoutSettings) -> WorkoutRecord {
+        let routine = find_routine(&self.routine_id, settings).unwrap_or_else(|| Routine {
+            id: self.routine_id.clone(),
+            name: self.routine_id.clone(),
+            ..Default::default()
+        });
+        WorkoutRecord {
+            rounds: self.rounds.clone(),
+            ..WorkoutRecord::new(
+                &routine,
+                &self.settings,
+                self.started_at(),
+                self.saved_at,
+                self.elapsed(),
+            )
+        }
+    }
+
+    pub fn save(&self) -> bool {
+        if let Some(window) = web_sys::window() {
+            if let Ok(Some(storage)) = window.local_storage() {
+                if let Ok(json) = serde_json::to_string(self) {
+                    return storage.set_item(SESSION_KEY, &json).is_ok();
+                }
+            }
+        }
+        false
+    }
+
+    /// Load the saved session, discarding it if it is unreadable. An expired
+    /// session is removed too, but returned so it can be logged.
+    pub fn load(now: DateTime<Utc>, resume_window_mins: u32) -> Option<SavedSession> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        let json = storage.get_item(SESSION_KEY).ok()??;
+        match serde_json::from_str::<WorkoutSession>(&json) {
+            Ok(session) => {
+                let saved = SavedSession::new(session, now, resume_window_mins);
+                if matches!(saved, SavedSession::Expired(_)) {
+                    let _ = storage.remove_item(SESSION_KEY);
+                }
+                Some(saved)
+            }
+            Err(_) => {
+                let _ = storage.remove_item(SESSION_KEY);
+                None
+            }
+        }
+    }
+
+    pub fn clear() {
+        if let Some(window) = web_sys::window() {
+            if let Ok(Some(storage)) = window.local_storage() {
+                let _ = storage.remove_item(SESSION_KEY);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn session_saved_at(saved_at: DateTime<Utc>) -> WorkoutSession {
+        WorkoutSession::new(
+            "4".to_string(),
+            &WorkoutSettings::default(),
+            Duration::from_secs(95),
+            true,
+            saved_at - chrono::Duration::minutes(5),
+            saved_at,
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_session_drops_history() {
+        let mut settings = WorkoutSettings::default();
+        let core = crate::data::routines::find_routine("3", &settings).unwrap();
+        settings
+            .history
+            .push(WorkoutRecord::completed(&core, &settings, Utc::now()));
+
+        let session = WorkoutSession::new(
+            "3".to_string(),
+            &settings,
+            Duration::ZERO,
+            false,
+            Utc::now(),
+            Utc::now(),
+            Vec::new(),
+        );
+        assert!(session.settings.history.is_empty());
+        assert_eq!(session.settings.sets, settings.sets);
+    }
+
+    #[test]
+    fn test_session_roundtrip() {
+        let session = session_saved_at(Utc.with_ymd_and_hms(2023, 5, 15, 10, 30, 0).unwrap());
+        let json = serde_json::to_string(&session).expect("Failed to serialize");
+        let deserialized: WorkoutSession = serde_json::from_str(&json).expect("Failed to deserialize");
+        assert_eq!(deserialized, session);
+        assert_eq!(deserialized.elapsed(), Duration::from_secs(95));
+    }
+
+    #[test]
+    fn test_session_started_at_fallback() {
+        let saved_at = Utc.with_ymd_and_hms(2023, 5, 15, 10, 30, 0).unwrap();
+        let session = WorkoutSession {
+            started_at: None,
+            ..session_saved_at(saved_at)
+        };
+        assert_eq!(session.started_at(), saved_at - chrono::Duration::seconds(95));
+        assert_eq!(
+            session_saved_at(saved_at).started_at(),
+            saved_at - chrono::Duration::minutes(5)
+        );
+    }
+
+    #[test]
+    fn test_session_expiry() {
+        let saved_at = Utc.with_ymd_and_hms(2023, 5, 15, 10, 30, 0).unwrap();
+        let session = session_saved_at(saved_at);
+
+        assert!(!session.is_expired(saved_at, 60));
+        assert!(!session.is_expired(saved_at + chrono::Duration::minutes(60), 60));
+        assert!(session.is_expired(saved_at + chrono::Duration::minutes(61), 60));
+    }
+
+    #[test]
+    fn test_expired_session_is_logged() {
+        let saved_at = Utc.with_ymd_and_hms(2023, 5, 15, 10, 30, 0).unwrap();
+        let session = session_saved_at(saved_at);
+        assert_eq!(
+            SavedSession::new(session.clone(), saved_at, 60),
+            SavedSession::Resumable(session.clone())
+        );
+        let saved = SavedSession::new(session.clone(), saved_at + chrono::Duration::minutes(61), 60);
+        assert_eq!(saved, SavedSession::Expired(session));
+
+        // Logged where it was left, 95s into Lower Body
+        let mut settings = WorkoutSettings::default();
+        let record = saved.into_session().record(&settings);
+        settings.history.push(record);
+        let record = &settings.history[0];
+        assert!(!record.finished);
+        assert_eq!(record.routine_name, "Lower Body");
+        assert_eq!(record.ended_at, saved_at);
+        assert_eq!(record.started_at, saved_at - chrono::Duration::minutes(5));
+        assert_eq!(record.stages_completed, 5);
+    }
+
+    #[test]
+    fn test_session_left_in_cool_down_is_finished() {
+        let settings = WorkoutSettings {
+            cool_down_secs: 120,
+            ..WorkoutSettings::default()
+        };
+        let routine = find_routine("4", &settings).unwrap();
+        let schedule = crate::workout::schedule::Schedule::new(&routine, &settings);
+        let in_cool_down = schedule.stage_start(schedule.main_stage_count()) + Duration::from_secs(30);
+        let now = Utc::now();
+        let session = WorkoutSession::new("4".to_string(), &settings, in_cool_down, true, now, now, Vec::new());
+        assert!(session.record(&settings).finished);
+    }
+}
@@ -47,6 +47,7 @@ TEXTS=(
     "Squats"
     "Squat to Toe Touch"
     "Superman"
+    "Switch Sides"
     "Three Two One"
     "Workout Complete"
     "Pigeon Pose"